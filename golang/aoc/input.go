@@ -8,7 +8,11 @@ import (
 )
 
 func Filename(day, challenge int) string {
-	return fmt.Sprintf("../inputs/day%02d-%d.txt", day, challenge)
+	profile := "default"
+	if challenge == 0 {
+		profile = "example"
+	}
+	return fmt.Sprintf("../inputs/2021/%s/day%02d.txt", profile, day)
 }
 
 func ReadFile(day, challenge int) string {
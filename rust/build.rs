@@ -0,0 +1,33 @@
+use std::env;
+use std::fs;
+use std::path::Path;
+
+#[path = "src/aoc/paths.rs"]
+mod paths;
+
+// Generates one test per line of the example table, see `aoc::examples`.
+fn main() {
+    let table = paths::examples_path(paths::YEAR);
+    println!("cargo:rerun-if-changed={}", table.display());
+    println!("cargo:rerun-if-changed=src/aoc/paths.rs");
+    let read = fs::read_to_string(&table).unwrap_or_default();
+    let tests = read
+        .lines()
+        .enumerate()
+        .filter(|(_, l)| !l.trim().is_empty() && !l.starts_with('#'))
+        .filter_map(|(i, l)| {
+            let mut fields = l.split_whitespace();
+            let day = fields.next()?.parse::<usize>().ok()?;
+            let part = fields.next()?.parse::<usize>().ok()?;
+            Some(format!(
+                "#[test]\nfn day{:02}_part{}_line{}() {{\n    super::check({});\n}}\n",
+                day,
+                part,
+                i + 1,
+                i + 1
+            ))
+        })
+        .collect::<String>();
+    let out = Path::new(&env::var("OUT_DIR").unwrap()).join("examples.rs");
+    fs::write(out, tests).unwrap();
+}
@@ -1,33 +1,468 @@
 use crate::aoc::input;
+use crate::aoc::schema::{Schema, Token};
 
-pub fn main(day: usize) {
-    println!("...1: {}", challenge1(day));
-    println!("...2: {}", challenge2(day));
+use serde::Serialize;
+use std::collections::{BTreeMap, VecDeque};
+use std::fmt;
+use std::io::{self, BufRead};
+
+pub fn main(key: &input::Key) {
+    println!("...1: {}", challenge1(key));
+    println!("...2: {}", challenge2(key));
+}
+
+pub fn challenge1(key: &input::Key) -> String {
+    match input::open(key).and_then(|r| count_increases_in(r, 1)) {
+        Ok(increases) => format!("Increasing depth: {} times", increases),
+        Err(err) => format!("Invalid depths: {}", err),
+    }
+}
+
+pub fn challenge2(key: &input::Key) -> String {
+    match input::open(key).and_then(|r| count_increases_in(r, 3)) {
+        Ok(increases) => format!("Increasing depth in windows of 3: {} times", increases),
+        Err(err) => format!("Invalid depths: {}", err),
+    }
+}
+
+pub fn schema() -> Schema {
+    Schema::lines(vec![Token::Number(0, i64::MAX)])
+}
+
+pub fn count_depth_increases_win(measurements: &[i64], window: usize) -> usize {
+    count_increases(measurements.iter().copied(), window - 1)
+}
+
+/// Counts how often the sum of `window` consecutive depths is larger than
+/// the previous sum. Neighbouring sums share all but their first and last
+/// depth, so only the last `window` depths are kept.
+#[derive(Debug, Clone)]
+pub struct IncreaseCounter {
+    window: usize,
+    recent: VecDeque<i64>,
+    increases: usize,
+}
+
+impl IncreaseCounter {
+    pub fn new(window: usize) -> IncreaseCounter {
+        assert!(window > 0, "window must hold at least one depth");
+        IncreaseCounter {
+            window,
+            recent: VecDeque::with_capacity(window),
+            increases: 0,
+        }
+    }
+
+    pub fn push(&mut self, depth: i64) {
+        if self.recent.len() == self.window {
+            let first = self.recent.pop_front().unwrap();
+            self.increases += (first < depth) as usize;
+        }
+        self.recent.push_back(depth);
+    }
+
+    pub fn increases(&self) -> usize {
+        self.increases
+    }
+}
+
+pub fn count_increases<I: IntoIterator<Item = i64>>(depths: I, window: usize) -> usize {
+    let mut counter = IncreaseCounter::new(window);
+    depths.into_iter().for_each(|d| counter.push(d));
+    counter.increases()
+}
+
+/// Parses one depth per line; a byte order mark at the start and blank lines
+/// are skipped, and errors name the offending line.
+pub fn depths<R: BufRead>(reader: R) -> impl Iterator<Item = io::Result<i64>> {
+    reader.lines().enumerate().filter_map(|(i, line)| {
+        let line = match line {
+            Ok(line) => line,
+            Err(err) => return Some(Err(err)),
+        };
+        let line = match i {
+            0 => line.strip_prefix('\u{feff}').unwrap_or(&line),
+            _ => &line,
+        };
+        let depth = line.trim();
+        if depth.is_empty() {
+            return None;
+        }
+        Some(depth.parse().map_err(|err| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("line {}: {:?}: {}", i + 1, depth, err),
+            )
+        }))
+    })
+}
+
+pub fn count_increases_in<R: BufRead>(reader: R, window: usize) -> io::Result<usize> {
+    let mut counter = IncreaseCounter::new(window);
+    for depth in depths(reader) {
+        counter.push(depth?);
+    }
+    Ok(counter.increases())
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Statistic {
+    Sum,
+    Mean,
+    Min,
+    Max,
+    Median,
+}
+
+impl Statistic {
+    /// The statistic of `values`, or `None` if there are none.
+    pub fn apply(&self, values: &[i64]) -> Option<f64> {
+        let mut window = Window::default();
+        values.iter().for_each(|&v| window.push_back(v));
+        window.statistic(*self)
+    }
+}
+
+/// Sorted values with their multiplicities.
+#[derive(Debug, Clone, Default)]
+struct Multiset {
+    counts: BTreeMap<i64, usize>,
+    len: usize,
+}
+
+impl Multiset {
+    fn insert(&mut self, value: i64) {
+        *self.counts.entry(value).or_insert(0) += 1;
+        self.len += 1;
+    }
+
+    fn remove(&mut self, value: i64) {
+        if let Some(count) = self.counts.get_mut(&value) {
+            *count -= 1;
+            if *count == 0 {
+                self.counts.remove(&value);
+            }
+            self.len -= 1;
+        }
+    }
+
+    fn first(&self) -> Option<i64> {
+        self.counts.keys().next().copied()
+    }
+
+    fn last(&self) -> Option<i64> {
+        self.counts.keys().next_back().copied()
+    }
+}
+
+/// Depths in arrival order, with a running sum and the lower and upper
+/// halves in sorted multisets, so every statistic costs at most a
+/// logarithmic update per depth instead of a pass over the window.
+#[derive(Debug, Clone, Default)]
+struct Window {
+    recent: VecDeque<i64>,
+    sum: i128,
+    lower: Multiset,
+    upper: Multiset,
+}
+
+impl Window {
+    fn len(&self) -> usize {
+        self.recent.len()
+    }
+
+    fn push_back(&mut self, depth: i64) {
+        self.recent.push_back(depth);
+        self.sum += depth as i128;
+        match self.lower.last() {
+            Some(max) if depth > max => self.upper.insert(depth),
+            _ => self.lower.insert(depth),
+        }
+        self.rebalance();
+    }
+
+    fn pop_front(&mut self) {
+        let depth = match self.recent.pop_front() {
+            Some(depth) => depth,
+            None => return,
+        };
+        self.sum -= depth as i128;
+        match self.lower.last() {
+            Some(max) if depth <= max => self.lower.remove(depth),
+            _ => self.upper.remove(depth),
+        }
+        self.rebalance();
+    }
+
+    /// Keeps `lower` holding the smaller half, and the middle value when
+    /// the length is odd.
+    fn rebalance(&mut self) {
+        while self.lower.len > self.upper.len + 1 {
+            let max = self.lower.last().unwrap();
+            self.lower.remove(max);
+            self.upper.insert(max);
+        }
+        while self.upper.len > self.lower.len {
+            let min = self.upper.first().unwrap();
+            self.upper.remove(min);
+            self.lower.insert(min);
+        }
+    }
+
+    fn statistic(&self, statistic: Statistic) -> Option<f64> {
+        let middle = self.lower.last()?;
+        Some(match statistic {
+            Statistic::Sum => self.sum as f64,
+            Statistic::Mean => self.sum as f64 / self.len() as f64,
+            Statistic::Min => self.lower.first()? as f64,
+            Statistic::Max => self.upper.last().unwrap_or(middle) as f64,
+            Statistic::Median => match self.len() % 2 {
+                0 => (middle as i128 + self.upper.first()? as i128) as f64 / 2.0,
+                _ => middle as f64,
+            },
+        })
+    }
+}
+
+/// A statistic over windows of `window` depths, starting a new window every
+/// `stride` depths. Windows that would run past the end are not reported.
+#[derive(Debug, Clone)]
+pub struct Rolling<I> {
+    depths: I,
+    statistic: Statistic,
+    window: usize,
+    stride: usize,
+    recent: Window,
+    seen: usize,
 }
 
-pub fn challenge1(day: usize) -> String {
-    let integers = input::as_ints(day, 1);
-    if let Ok(integers) = integers {
-        let increases = count_depth_increases_win(&integers, 2);
-        return format!("Increasing depth: {} times", increases);
+pub fn rolling<I: IntoIterator<Item = i64>>(
+    depths: I,
+    statistic: Statistic,
+    window: usize,
+    stride: usize,
+) -> Rolling<I::IntoIter> {
+    assert!(
+        window > 0 && stride > 0,
+        "window and stride must be positive"
+    );
+    Rolling {
+        depths: depths.into_iter(),
+        statistic,
+        window,
+        stride,
+        recent: Window::default(),
+        seen: 0,
     }
-    "".to_string()
 }
 
-pub fn challenge2(day: usize) -> String {
-    let integers = input::as_ints(day, 1);
-    if let Ok(integers) = integers {
-        let increases = count_depth_increases_win(&integers, 4);
-        return format!("Increasing depth in windows of 3: {} times", increases);
+impl<I: Iterator<Item = i64>> Iterator for Rolling<I> {
+    type Item = f64;
+
+    fn next(&mut self) -> Option<f64> {
+        loop {
+            let depth = self.depths.next()?;
+            self.seen += 1;
+            if self.recent.len() == self.window {
+                self.recent.pop_front();
+            }
+            self.recent.push_back(depth);
+            let start = self.seen - self.recent.len();
+            if self.recent.len() == self.window && start.is_multiple_of(self.stride) {
+                return self.recent.statistic(self.statistic);
+            }
+        }
+    }
+}
+
+/// How consecutive values compare to their predecessor.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Trend {
+    pub increases: usize,
+    pub decreases: usize,
+    pub plateaus: usize,
+}
+
+pub fn trend<I: IntoIterator<Item = f64>>(values: I) -> Trend {
+    let mut trend = Trend::default();
+    let mut previous: Option<f64> = None;
+    for value in values {
+        if let Some(previous) = previous {
+            match value.partial_cmp(&previous) {
+                Some(std::cmp::Ordering::Greater) => trend.increases += 1,
+                Some(std::cmp::Ordering::Less) => trend.decreases += 1,
+                _ => trend.plateaus += 1,
+            }
+        }
+        previous = Some(value);
     }
-    "".to_string()
+    trend
+}
+
+/// Consecutive depths, `len` of them starting at index `start`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct Run {
+    pub start: usize,
+    pub len: usize,
 }
 
-fn count_depth_increases_win(measurements: &Vec<i64>, window: usize) -> usize {
-    measurements
-        .windows(window)
-        .filter(|w| w.first() < w.last())
-        .count()
+/// A depth that differs from the mean of the preceding window by more than
+/// the threshold.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct Spike {
+    pub index: usize,
+    pub depth: i64,
+    pub baseline: f64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct Trench {
+    pub run: Run,
+    pub mean: f64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Report {
+    pub measurements: usize,
+    pub longest_increase: Option<Run>,
+    pub longest_decrease: Option<Run>,
+    pub plateaus: Vec<Run>,
+    pub spikes: Vec<Spike>,
+    /// The window with the greatest mean depth.
+    pub deepest_trench: Option<Trench>,
+}
+
+/// Tracks the run of consecutive depths that all compare the same way to
+/// their predecessor, keeping the first longest one.
+#[derive(Debug, Default)]
+struct RunTracker {
+    current: Option<Run>,
+    longest: Option<Run>,
+}
+
+impl RunTracker {
+    fn extend(&mut self, index: usize, continues: bool) {
+        self.current = match (self.current, continues) {
+            (Some(run), true) => Some(Run {
+                len: run.len + 1,
+                ..run
+            }),
+            (None, true) => Some(Run {
+                start: index - 1,
+                len: 2,
+            }),
+            (_, false) => None,
+        };
+        if let Some(run) = self.current {
+            if self.longest.map(|l| run.len > l.len).unwrap_or(true) {
+                self.longest = Some(run);
+            }
+        }
+    }
+}
+
+/// Scans the depths once, keeping only the last `window` of them, and
+/// reports runs, plateaus, spikes beyond `threshold` and the deepest window.
+pub fn profile<I: IntoIterator<Item = i64>>(depths: I, window: usize, threshold: f64) -> Report {
+    assert!(window > 0, "window must hold at least one depth");
+    let mut increase = RunTracker::default();
+    let mut decrease = RunTracker::default();
+    let mut plateaus: Vec<Run> = vec![];
+    let mut spikes = vec![];
+    let mut deepest_trench: Option<Trench> = None;
+    let mut recent: VecDeque<i64> = VecDeque::with_capacity(window + 1);
+    let mut sum: i128 = 0;
+    let mut measurements = 0;
+    for (index, depth) in depths.into_iter().enumerate() {
+        measurements += 1;
+        if let Some(&previous) = recent.back() {
+            increase.extend(index, depth > previous);
+            decrease.extend(index, depth < previous);
+            if depth == previous {
+                match plateaus.last_mut() {
+                    Some(run) if run.start + run.len == index => run.len += 1,
+                    _ => plateaus.push(Run {
+                        start: index - 1,
+                        len: 2,
+                    }),
+                }
+            }
+        }
+        if recent.len() == window {
+            let baseline = sum as f64 / window as f64;
+            if (depth as f64 - baseline).abs() > threshold {
+                spikes.push(Spike {
+                    index,
+                    depth,
+                    baseline,
+                });
+            }
+            sum -= recent.pop_front().unwrap() as i128;
+        }
+        recent.push_back(depth);
+        sum += depth as i128;
+        if recent.len() == window {
+            let mean = sum as f64 / window as f64;
+            if deepest_trench.map(|t| mean > t.mean).unwrap_or(true) {
+                deepest_trench = Some(Trench {
+                    run: Run {
+                        start: index + 1 - window,
+                        len: window,
+                    },
+                    mean,
+                });
+            }
+        }
+    }
+    Report {
+        measurements,
+        longest_increase: increase.longest,
+        longest_decrease: decrease.longest,
+        plateaus,
+        spikes,
+        deepest_trench,
+    }
+}
+
+impl fmt::Display for Run {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} depths from #{}", self.len, self.start)
+    }
+}
+
+impl fmt::Display for Report {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let run = |run: &Option<Run>| match run {
+            Some(run) => run.to_string(),
+            None => "none".to_string(),
+        };
+        let list = |items: Vec<String>| match items.is_empty() {
+            true => "none".to_string(),
+            false => items.join(", "),
+        };
+        writeln!(f, "Measurements: {}", self.measurements)?;
+        writeln!(f, "Longest increase: {}", run(&self.longest_increase))?;
+        writeln!(f, "Longest decrease: {}", run(&self.longest_decrease))?;
+        writeln!(
+            f,
+            "Plateaus: {}",
+            list(self.plateaus.iter().map(|p| p.to_string()).collect())
+        )?;
+        writeln!(
+            f,
+            "Spikes: {}",
+            list(
+                self.spikes
+                    .iter()
+                    .map(|s| format!("{} at #{} (baseline {:.1})", s.depth, s.index, s.baseline))
+                    .collect()
+            )
+        )?;
+        match self.deepest_trench {
+            Some(t) => write!(f, "Deepest trench: {}, mean {:.1}", t.run, t.mean),
+            None => write!(f, "Deepest trench: none"),
+        }
+    }
 }
 
 #[cfg(test)]
@@ -47,4 +482,198 @@ mod tests {
         let count = count_depth_increases_win(&numbers, 4);
         assert_eq!(count, 5);
     }
+
+    #[test]
+    fn count_increases_works() {
+        let numbers = vec![199, 200, 208, 210, 200, 207, 240, 269, 260, 263];
+        for window in 1..=12 {
+            let expected = numbers
+                .windows(window)
+                .map(|w| w.iter().sum::<i64>())
+                .collect::<Vec<_>>()
+                .windows(2)
+                .filter(|w| w[0] < w[1])
+                .count();
+            assert_eq!(count_increases(numbers.clone(), window), expected);
+        }
+        assert_eq!(count_increases(Vec::new(), 3), 0);
+    }
+
+    #[test]
+    fn count_increases_in_works() {
+        let reader = io::Cursor::new("\u{feff}199\r\n200\r\n208\n\n210\n200");
+        assert_eq!(count_increases_in(reader, 1).unwrap(), 3);
+        let reader = input::open(&input::Key::example(1)).unwrap();
+        assert_eq!(count_increases_in(reader, 3).unwrap(), 5);
+        let err = count_increases_in(io::Cursor::new("1\n2\nx3\n"), 1).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        assert!(err.to_string().starts_with("line 3: \"x3\""));
+        let err = count_increases_in(io::Cursor::new("1\n\u{feff}2\n"), 1).unwrap_err();
+        assert!(err.to_string().starts_with("line 2: "));
+    }
+
+    #[test]
+    fn invalid_depths() {
+        let key = input::Key::new(input::YEAR, 1, "invalid");
+        input::provide_inline(&key, "199\n200\ndeep\n");
+        assert!(super::challenge1(&key).starts_with("Invalid depths: line 3: \"deep\""));
+        assert!(super::challenge2(&key).starts_with("Invalid depths: line 3: \"deep\""));
+    }
+
+    #[test]
+    fn rolling_works() {
+        let numbers = vec![199, 200, 208, 210, 200, 207, 240, 269, 260, 263];
+        let sums = rolling(numbers.clone(), Statistic::Sum, 3, 1).collect::<Vec<_>>();
+        assert_eq!(
+            sums,
+            vec![607.0, 618.0, 618.0, 617.0, 647.0, 716.0, 769.0, 792.0]
+        );
+        let stats = |statistic, window, stride| {
+            rolling(numbers.clone(), statistic, window, stride).collect::<Vec<_>>()
+        };
+        assert_eq!(stats(Statistic::Mean, 2, 4), vec![199.5, 203.5, 261.5]);
+        assert_eq!(stats(Statistic::Min, 4, 3), vec![199.0, 200.0, 240.0]);
+        assert_eq!(stats(Statistic::Max, 5, 5), vec![210.0, 269.0]);
+        assert_eq!(
+            stats(Statistic::Median, 3, 2),
+            vec![200.0, 208.0, 207.0, 260.0]
+        );
+        assert_eq!(stats(Statistic::Median, 4, 6), vec![204.0, 261.5]);
+        assert_eq!(stats(Statistic::Sum, 11, 1), Vec::<f64>::new());
+    }
+
+    #[test]
+    fn rolling_matches_apply() {
+        let numbers = (0..200)
+            .map(|i: i64| (i * 7919) % 31 - 15)
+            .collect::<Vec<_>>();
+        let statistics = [
+            Statistic::Sum,
+            Statistic::Mean,
+            Statistic::Min,
+            Statistic::Max,
+            Statistic::Median,
+        ];
+        for statistic in statistics {
+            for window in [1, 2, 5, 16] {
+                let expected = numbers
+                    .windows(window)
+                    .map(|w| {
+                        let mut sorted = w.to_vec();
+                        sorted.sort_unstable();
+                        let mid = window / 2;
+                        match statistic {
+                            Statistic::Sum => w.iter().sum::<i64>() as f64,
+                            Statistic::Mean => w.iter().sum::<i64>() as f64 / window as f64,
+                            Statistic::Min => sorted[0] as f64,
+                            Statistic::Max => sorted[window - 1] as f64,
+                            Statistic::Median if window % 2 == 0 => {
+                                (sorted[mid - 1] + sorted[mid]) as f64 / 2.0
+                            }
+                            Statistic::Median => sorted[mid] as f64,
+                        }
+                    })
+                    .collect::<Vec<_>>();
+                let rolled = rolling(numbers.clone(), statistic, window, 1).collect::<Vec<_>>();
+                assert_eq!(rolled, expected, "{:?} over {}", statistic, window);
+            }
+        }
+        assert_eq!(Statistic::Median.apply(&[]), None);
+        assert_eq!(Statistic::Mean.apply(&[]), None);
+        assert_eq!(
+            Statistic::Max.apply(&[i64::MAX, i64::MAX]),
+            Some(i64::MAX as f64)
+        );
+        assert_eq!(
+            Statistic::Median.apply(&[i64::MAX, i64::MAX]),
+            Some(i64::MAX as f64)
+        );
+    }
+
+    #[test]
+    fn trend_works() {
+        let numbers = vec![199, 200, 208, 210, 200, 207, 240, 269, 260, 263];
+        for window in 1..=4 {
+            let sums = rolling(numbers.clone(), Statistic::Sum, window, 1);
+            assert_eq!(
+                trend(sums).increases,
+                count_increases(numbers.clone(), window)
+            );
+        }
+        assert_eq!(
+            trend(rolling(numbers, Statistic::Sum, 3, 1)),
+            Trend {
+                increases: 5,
+                decreases: 1,
+                plateaus: 1
+            }
+        );
+        assert_eq!(trend(vec![]), Trend::default());
+    }
+
+    #[test]
+    fn profile_works() {
+        let numbers = vec![199, 200, 208, 210, 200, 207, 240, 269, 260, 263];
+        let report = profile(numbers, 3, 20.0);
+        assert_eq!(report.measurements, 10);
+        assert_eq!(report.longest_increase, Some(Run { start: 0, len: 4 }));
+        assert_eq!(report.longest_decrease, Some(Run { start: 3, len: 2 }));
+        assert_eq!(report.plateaus, vec![]);
+        assert_eq!(
+            report.spikes.iter().map(|s| s.index).collect::<Vec<_>>(),
+            vec![6, 7, 8]
+        );
+        assert_eq!(
+            report.deepest_trench,
+            Some(Trench {
+                run: Run { start: 7, len: 3 },
+                mean: 264.0
+            })
+        );
+        assert_eq!(
+            report.to_string(),
+            [
+                "Measurements: 10",
+                "Longest increase: 4 depths from #0",
+                "Longest decrease: 2 depths from #3",
+                "Plateaus: none",
+                "Spikes: 240 at #6 (baseline 205.7), 269 at #7 (baseline 215.7), 260 at #8 (baseline 238.7)",
+                "Deepest trench: 3 depths from #7, mean 264.0",
+            ]
+            .join("\n")
+        );
+    }
+
+    #[test]
+    fn profile_finds_plateaus() {
+        let report = profile(vec![5, 5, 5, 4, 3, 3, 2, 1, 0, 0], 2, 100.0);
+        assert_eq!(
+            report.plateaus,
+            vec![
+                Run { start: 0, len: 3 },
+                Run { start: 4, len: 2 },
+                Run { start: 8, len: 2 }
+            ]
+        );
+        assert_eq!(report.longest_decrease, Some(Run { start: 5, len: 4 }));
+        assert_eq!(report.longest_increase, None);
+        assert_eq!(report.spikes, vec![]);
+        assert_eq!(profile(vec![], 2, 1.0).deepest_trench, None);
+    }
+
+    #[test]
+    fn profile_serializes() {
+        let report = profile(vec![3, 3, 1, 9], 2, 4.0);
+        assert_eq!(
+            serde_json::to_value(&report).unwrap(),
+            serde_json::json!({
+                "measurements": 4,
+                "longest_increase": { "start": 2, "len": 2 },
+                "longest_decrease": { "start": 1, "len": 2 },
+                "plateaus": [{ "start": 0, "len": 2 }],
+                "spikes": [{ "index": 3, "depth": 9, "baseline": 2.0 }],
+                "deepest_trench": { "run": { "start": 2, "len": 2 }, "mean": 5.0 }
+            })
+        );
+    }
 }
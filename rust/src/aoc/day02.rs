@@ -1,82 +1,1017 @@
 use crate::aoc::input;
+use crate::aoc::schema::{Schema, Token};
 
-pub fn main(day: usize) {
-    println!("...1: {}", challenge1(day));
-    println!("...2: {}", challenge2(day));
-}
-
-pub fn challenge1(day: usize) -> String {
-    let steps = input::read_file(day, 1);
-    let lines = steps.lines().collect();
-    let (horiz, depth) = plan_course_with_aim(&lines, false);
-    return format!("Horizontal {} * depth {} = {}", horiz, depth, horiz * depth);
-}
-
-pub fn challenge2(day: usize) -> String {
-    let steps = input::read_file(day, 1);
-    let lines = steps.lines().collect();
-    let (horiz, depth) = plan_course_with_aim(&lines, true);
-    return format!("Horizontal {} * depth {} = {}", horiz, depth, horiz * depth);
-}
-
-fn plan_course_with_aim(measurements: &Vec<&str>, include_aim: bool) -> (usize, usize) {
-    let mut horiz: usize = 0;
-    let mut depth: usize = 0;
-    let mut aim: usize = 0;
-    measurements.iter().for_each(|l| {
-        let mut line = l.split(' ');
-        let cmd = line.next();
-        let x = line.next().unwrap().parse::<usize>().unwrap();
-        match cmd {
-            Some("forward") => {
-                horiz += x;
-                if include_aim {
-                    depth += aim * x;
+extern crate pest;
+use pest::Parser;
+use std::collections::HashMap;
+use std::fmt;
+use std::str::FromStr;
+
+pub fn main(key: &input::Key) {
+    println!("...1: {}", challenge1(key));
+    println!("...2: {}", challenge2(key));
+}
+
+pub fn challenge1(key: &input::Key) -> String {
+    solve(key, &Simple, Surface::Allow)
+}
+
+pub fn challenge2(key: &input::Key) -> String {
+    solve(key, &Aim, Surface::Allow)
+}
+
+pub fn solve(key: &input::Key, model: &dyn Navigator, surface: Surface) -> String {
+    match read_commands(&input::read_file(key)) {
+        Ok(commands) => model.describe(&commands, surface),
+        Err(err) => format!("Invalid command file: {}", err),
+    }
+}
+
+/// The puzzle answer for a planned course, or why there is none.
+pub fn describe<S: NavigationState>(course: Result<S, NavigationError>) -> String {
+    match course.and_then(|s| s.product().map(|product| (s, product))) {
+        Ok((s, product)) => format!(
+            "Horizontal {} * depth {} = {}",
+            s.horiz(),
+            s.depth(),
+            product
+        ),
+        Err(err) => format!("Navigation failed: {}", err),
+    }
+}
+
+pub fn schema() -> Schema {
+    Schema::lines(vec![
+        Token::OneOf(&["forward", "down", "up"]),
+        Token::Literal(" "),
+        Token::Number(0, i64::MAX),
+    ])
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Command {
+    Forward(usize),
+    Down(usize),
+    Up(usize),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ParseErrorKind {
+    EmptyLine,
+    UnknownCommand,
+    InvalidAmount,
+    MissingAmount,
+    TrailingToken,
+}
+
+/// Where and why a command line was rejected. Parsing a single command
+/// reports line 1; `read_commands` fills in the real line number.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    pub line: usize,
+    pub token: String,
+    pub kind: ParseErrorKind,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let reason = match self.kind {
+            ParseErrorKind::EmptyLine => return write!(f, "line {}: empty line", self.line),
+            ParseErrorKind::UnknownCommand => "unknown command",
+            ParseErrorKind::InvalidAmount => "invalid amount",
+            ParseErrorKind::MissingAmount => "missing amount after",
+            ParseErrorKind::TrailingToken => "unexpected",
+        };
+        write!(f, "line {}: {} {:?}", self.line, reason, self.token)
+    }
+}
+
+impl FromStr for Command {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Command, ParseError> {
+        let error = |token: &str, kind| ParseError {
+            line: 1,
+            token: token.to_string(),
+            kind,
+        };
+        let mut tokens = s.split_whitespace();
+        let name = tokens.next().unwrap_or("");
+        let command: fn(usize) -> Command = match name {
+            "forward" => Command::Forward,
+            "down" => Command::Down,
+            "up" => Command::Up,
+            "" => return Err(error(name, ParseErrorKind::EmptyLine)),
+            _ => return Err(error(name, ParseErrorKind::UnknownCommand)),
+        };
+        let amount = match tokens.next() {
+            Some(amount) => amount
+                .parse()
+                .map_err(|_| error(amount, ParseErrorKind::InvalidAmount))?,
+            None => return Err(error(name, ParseErrorKind::MissingAmount)),
+        };
+        match tokens.next() {
+            Some(token) => Err(error(token, ParseErrorKind::TrailingToken)),
+            None => Ok(command(amount)),
+        }
+    }
+}
+
+pub fn read_commands(read: &str) -> Result<Vec<Command>, ParseError> {
+    read.lines()
+        .enumerate()
+        .map(|(i, l)| l.parse().map_err(|err| ParseError { line: i + 1, ..err }))
+        .collect()
+}
+
+/// What to do when a command would take the submarine above the surface.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Surface {
+    /// Negative depths are kept, the submarine flies.
+    Allow,
+    /// Depth stops at 0; aim is still free to go negative.
+    Clamp,
+    Error,
+}
+
+impl FromStr for Surface {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Surface, String> {
+        match s {
+            "allow" => Ok(Surface::Allow),
+            "clamp" => Ok(Surface::Clamp),
+            "error" => Ok(Surface::Error),
+            _ => Err(format!(
+                "unknown surface mode {:?}, expected allow, clamp or error",
+                s
+            )),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Position {
+    pub horiz: i64,
+    pub depth: i64,
+    pub aim: i64,
+}
+
+/// The state a navigation model keeps between commands. Every state has a
+/// horizontal position and a depth, for the answer, the surface check and
+/// the SVG trace; `fields` lists all of its values for the CSV trace.
+pub trait NavigationState: Clone + Default {
+    fn horiz(&self) -> i64;
+    fn depth(&self) -> i64;
+    fn set_depth(&mut self, depth: i64);
+    fn fields(&self) -> Vec<(&'static str, i64)>;
+
+    /// Horizontal position times depth.
+    fn product(&self) -> Result<i64, NavigationError> {
+        self.horiz()
+            .checked_mul(self.depth())
+            .ok_or(NavigationError::ProductOverflow {
+                horiz: self.horiz(),
+                depth: self.depth(),
+            })
+    }
+}
+
+impl NavigationState for Position {
+    fn horiz(&self) -> i64 {
+        self.horiz
+    }
+
+    fn depth(&self) -> i64 {
+        self.depth
+    }
+
+    fn set_depth(&mut self, depth: i64) {
+        self.depth = depth;
+    }
+
+    fn fields(&self) -> Vec<(&'static str, i64)> {
+        vec![
+            ("horiz", self.horiz),
+            ("depth", self.depth),
+            ("aim", self.aim),
+        ]
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum NavigationError {
+    AboveSurface {
+        step: usize,
+        command: Command,
+        depth: i64,
+    },
+    Overflow {
+        step: usize,
+        command: Command,
+    },
+    ProductOverflow {
+        horiz: i64,
+        depth: i64,
+    },
+}
+
+impl fmt::Display for NavigationError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            NavigationError::AboveSurface {
+                step,
+                command,
+                depth,
+            } => write!(
+                f,
+                "command {} ({}) takes the submarine above the surface to depth {}",
+                step, command, depth
+            ),
+            NavigationError::Overflow { step, command } => {
+                write!(f, "command {} ({}) overflows the position", step, command)
+            }
+            NavigationError::ProductOverflow { horiz, depth } => write!(
+                f,
+                "horizontal {} * depth {} overflows the answer",
+                horiz, depth
+            ),
+        }
+    }
+}
+
+impl fmt::Display for Command {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Command::Forward(x) => write!(f, "forward {}", x),
+            Command::Down(x) => write!(f, "down {}", x),
+            Command::Up(x) => write!(f, "up {}", x),
+        }
+    }
+}
+
+/// An interpretation of the submarine commands over its own kind of state.
+/// `apply` returns `None` when the new state does not fit in an `i64`.
+pub trait NavigationModel {
+    type State: NavigationState;
+
+    fn name(&self) -> &'static str;
+    fn apply(&self, state: &Self::State, command: Command) -> Option<Self::State>;
+}
+
+/// `down` and `up` change the depth directly.
+pub struct Simple;
+
+/// `down` and `up` turn the submarine; `forward` dives along the aim.
+pub struct Aim;
+
+fn amount(x: usize) -> Option<i64> {
+    i64::try_from(x).ok()
+}
+
+impl NavigationModel for Simple {
+    type State = Position;
+
+    fn name(&self) -> &'static str {
+        "simple"
+    }
+
+    fn apply(&self, p: &Position, command: Command) -> Option<Position> {
+        Some(match command {
+            Command::Forward(x) => Position {
+                horiz: p.horiz.checked_add(amount(x)?)?,
+                ..*p
+            },
+            Command::Down(x) => Position {
+                depth: p.depth.checked_add(amount(x)?)?,
+                ..*p
+            },
+            Command::Up(x) => Position {
+                depth: p.depth.checked_sub(amount(x)?)?,
+                ..*p
+            },
+        })
+    }
+}
+
+impl NavigationModel for Aim {
+    type State = Position;
+
+    fn name(&self) -> &'static str {
+        "aim"
+    }
+
+    fn apply(&self, p: &Position, command: Command) -> Option<Position> {
+        Some(match command {
+            Command::Forward(x) => Position {
+                horiz: p.horiz.checked_add(amount(x)?)?,
+                depth: p.depth.checked_add(p.aim.checked_mul(amount(x)?)?)?,
+                ..*p
+            },
+            Command::Down(x) => Position {
+                aim: p.aim.checked_add(amount(x)?)?,
+                ..*p
+            },
+            Command::Up(x) => Position {
+                aim: p.aim.checked_sub(amount(x)?)?,
+                ..*p
+            },
+        })
+    }
+}
+
+/// A navigation model with its state type hidden, so that models can be
+/// picked by name at run time.
+pub trait Navigator {
+    fn name(&self) -> &'static str;
+    /// The puzzle answer for the course, or why there is none.
+    fn describe(&self, commands: &[Command], surface: Surface) -> String;
+    /// Like `describe`, also returning the recorded course.
+    fn record(&self, commands: &[Command], surface: Surface) -> (Box<dyn Export>, String);
+}
+
+impl<M> Navigator for M
+where
+    M: NavigationModel,
+    M::State: 'static,
+{
+    fn name(&self) -> &'static str {
+        NavigationModel::name(self)
+    }
+
+    fn describe(&self, commands: &[Command], surface: Surface) -> String {
+        describe(plan_course(commands, self, surface))
+    }
+
+    fn record(&self, commands: &[Command], surface: Surface) -> (Box<dyn Export>, String) {
+        let (trace, result) = trace(commands, self, surface);
+        (Box::new(trace), describe(result))
+    }
+}
+
+/// The built-in models, selectable by name.
+pub fn models() -> Vec<Box<dyn Navigator>> {
+    vec![Box::new(Simple), Box::new(Aim)]
+}
+
+pub fn model(name: &str) -> Option<Box<dyn Navigator>> {
+    models().into_iter().find(|m| m.name() == name)
+}
+
+pub fn plan_course<M: NavigationModel>(
+    commands: &[Command],
+    model: &M,
+    surface: Surface,
+) -> Result<M::State, NavigationError> {
+    navigate(commands, model, surface, |_, _| {})
+}
+
+/// Applies the commands in order, calling `visit` with each command and
+/// the state after it.
+pub fn navigate<M, F>(
+    commands: &[Command],
+    model: &M,
+    surface: Surface,
+    mut visit: F,
+) -> Result<M::State, NavigationError>
+where
+    M: NavigationModel,
+    F: FnMut(&Command, &M::State),
+{
+    let mut state = M::State::default();
+    for (i, command) in commands.iter().enumerate() {
+        state = model
+            .apply(&state, *command)
+            .ok_or(NavigationError::Overflow {
+                step: i + 1,
+                command: *command,
+            })?;
+        if state.depth() < 0 {
+            match surface {
+                Surface::Allow => {}
+                Surface::Clamp => state.set_depth(0),
+                Surface::Error => {
+                    return Err(NavigationError::AboveSurface {
+                        step: i + 1,
+                        command: *command,
+                        depth: state.depth(),
+                    })
                 }
             }
-            Some("down") => aim += x,
-            Some("up") => aim -= x,
-            Some(&_) => {}
-            None => {}
         }
+        visit(command, &state);
+    }
+    Ok(state)
+}
+
+/// Every state of a course, starting at the origin.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Trace<S> {
+    pub steps: Vec<(Option<Command>, S)>,
+}
+
+/// Records the course up to the end or up to the command that failed.
+pub fn trace<M: NavigationModel>(
+    commands: &[Command],
+    model: &M,
+    surface: Surface,
+) -> (Trace<M::State>, Result<M::State, NavigationError>) {
+    let mut steps = vec![(None, M::State::default())];
+    let result = navigate(commands, model, surface, |c, s| {
+        steps.push((Some(*c), s.clone()))
     });
-    if include_aim {
-        aim = depth;
+    (Trace { steps }, result)
+}
+
+/// The file formats a recorded course can be written as.
+pub trait Export {
+    fn to_csv(&self) -> String;
+    fn to_svg(&self) -> String;
+}
+
+impl<S: NavigationState> Export for Trace<S> {
+    /// One column per state field, named after it.
+    fn to_csv(&self) -> String {
+        let names = S::default()
+            .fields()
+            .iter()
+            .map(|(name, _)| *name)
+            .collect::<Vec<_>>();
+        let mut csv = format!("step,command,{}\n", names.join(","));
+        for (i, (command, state)) in self.steps.iter().enumerate() {
+            let command = command.map(|c| c.to_string()).unwrap_or_default();
+            let values = state
+                .fields()
+                .iter()
+                .map(|(_, v)| v.to_string())
+                .collect::<Vec<_>>();
+            csv += &format!("{},{},{}\n", i, command, values.join(","));
+        }
+        csv
+    }
+
+    /// Depth against horizontal position, with depth growing downwards like
+    /// the sea and the surface drawn as a line.
+    fn to_svg(&self) -> String {
+        let xs = self.steps.iter().map(|(_, s)| s.horiz());
+        let ys = self.steps.iter().map(|(_, s)| s.depth()).chain([0]);
+        let (min_x, max_x) = (xs.clone().min().unwrap(), xs.max().unwrap());
+        let (min_y, max_y) = (ys.clone().min().unwrap(), ys.max().unwrap());
+        let points = self
+            .steps
+            .iter()
+            .map(|(_, s)| format!("{},{}", s.horiz(), s.depth()))
+            .collect::<Vec<_>>()
+            .join(" ");
+        [
+            format!(
+                "<svg xmlns=\"http://www.w3.org/2000/svg\" viewBox=\"{} {} {} {}\" width=\"800\" height=\"400\" preserveAspectRatio=\"none\">",
+                min_x,
+                min_y,
+                (max_x - min_x).max(1),
+                (max_y - min_y).max(1)
+            ),
+            format!(
+                "  <line x1=\"{}\" y1=\"0\" x2=\"{}\" y2=\"0\" stroke=\"steelblue\" vector-effect=\"non-scaling-stroke\"/>",
+                min_x, max_x
+            ),
+            format!(
+                "  <polyline points=\"{}\" fill=\"none\" stroke=\"black\" vector-effect=\"non-scaling-stroke\"/>",
+                points
+            ),
+            "</svg>".to_string(),
+        ]
+        .join("\n")
+            + "\n"
+    }
+}
+
+#[derive(pest_derive::Parser)]
+#[grammar = "aoc/day02.pest"]
+pub struct ScriptParser;
+
+/// A statement of a command script. Plain command files are scripts too.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Statement {
+    Command { command: Command, line: usize },
+    Repeat(usize, Vec<Statement>),
+    Call { name: String, line: usize },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Script {
+    pub macros: HashMap<String, Vec<Statement>>,
+    pub statements: Vec<Statement>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ScriptError {
+    Syntax(Box<pest::error::Error<Rule>>),
+    InvalidAmount { token: String, line: usize },
+    DuplicateMacro { name: String, line: usize },
+    UnknownMacro { name: String, line: usize },
+    RecursiveMacro { name: String, line: usize },
+    TooManyCommands { limit: usize, line: usize },
+}
+
+impl fmt::Display for ScriptError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ScriptError::Syntax(err) => write!(f, "{}", err),
+            ScriptError::InvalidAmount { token, line } => {
+                write!(f, "line {}: invalid amount {:?}", line, token)
+            }
+            ScriptError::DuplicateMacro { name, line } => {
+                write!(f, "line {}: macro {:?} is already defined", line, name)
+            }
+            ScriptError::UnknownMacro { name, line } => {
+                write!(f, "line {}: unknown macro {:?}", line, name)
+            }
+            ScriptError::RecursiveMacro { name, line } => {
+                write!(f, "line {}: macro {:?} calls itself", line, name)
+            }
+            ScriptError::TooManyCommands { limit, line } => {
+                write!(
+                    f,
+                    "line {}: script expands to more than {} commands",
+                    line, limit
+                )
+            }
+        }
+    }
+}
+
+type Pair<'a> = pest::iterators::Pair<'a, Rule>;
+
+fn amount_of(pair: Pair) -> Result<usize, ScriptError> {
+    pair.as_str()
+        .parse()
+        .map_err(|_| ScriptError::InvalidAmount {
+            token: pair.as_str().to_string(),
+            line: pair.as_span().start_pos().line_col().0,
+        })
+}
+
+fn statement(pair: Pair) -> Result<Statement, ScriptError> {
+    let line = pair.as_span().start_pos().line_col().0;
+    match pair.as_rule() {
+        Rule::command => {
+            let mut inner = pair.into_inner();
+            let direction = inner.next().unwrap().as_str();
+            let amount = amount_of(inner.next().unwrap())?;
+            let command = match direction {
+                "forward" => Command::Forward(amount),
+                "down" => Command::Down(amount),
+                _ => Command::Up(amount),
+            };
+            Ok(Statement::Command { command, line })
+        }
+        Rule::repeat => {
+            let mut inner = pair.into_inner();
+            let times = amount_of(inner.next().unwrap())?;
+            Ok(Statement::Repeat(times, block(inner.next().unwrap())?))
+        }
+        Rule::call => Ok(Statement::Call {
+            name: pair.as_str().to_string(),
+            line,
+        }),
+        rule => unreachable!("unexpected rule {:?}", rule),
+    }
+}
+
+fn block(pair: Pair) -> Result<Vec<Statement>, ScriptError> {
+    pair.into_inner().map(statement).collect()
+}
+
+impl FromStr for Script {
+    type Err = ScriptError;
+
+    fn from_str(s: &str) -> Result<Script, ScriptError> {
+        let script = ScriptParser::parse(Rule::script, s)
+            .map_err(|err| ScriptError::Syntax(Box::new(err)))?
+            .next()
+            .unwrap();
+        let mut macros = HashMap::new();
+        let mut statements = vec![];
+        for pair in script.into_inner() {
+            match pair.as_rule() {
+                Rule::definition => {
+                    let line = pair.as_span().start_pos().line_col().0;
+                    let mut inner = pair.into_inner();
+                    let name = inner.next().unwrap().as_str().to_string();
+                    let body = block(inner.next().unwrap())?;
+                    if macros.insert(name.clone(), body).is_some() {
+                        return Err(ScriptError::DuplicateMacro { name, line });
+                    }
+                }
+                Rule::EOI => {}
+                _ => statements.push(statement(pair)?),
+            }
+        }
+        Ok(Script { macros, statements })
     }
-    (horiz, aim)
+}
+
+/// Most commands a script may expand to, so that nested repeats cannot
+/// exhaust memory.
+pub const MAX_COMMANDS: usize = 1_000_000;
+
+impl Script {
+    /// The plain command stream the script stands for. Macros may be called
+    /// before their definition, but not from within themselves, and the
+    /// stream may hold at most `MAX_COMMANDS` commands.
+    pub fn expand(&self) -> Result<Vec<Command>, ScriptError> {
+        let mut commands = vec![];
+        self.expand_into(&self.statements, &mut vec![], &mut commands)?;
+        Ok(commands)
+    }
+
+    fn expand_into(
+        &self,
+        statements: &[Statement],
+        calls: &mut Vec<String>,
+        commands: &mut Vec<Command>,
+    ) -> Result<(), ScriptError> {
+        for statement in statements {
+            match statement {
+                Statement::Command { command, line } => {
+                    if commands.len() == MAX_COMMANDS {
+                        return Err(ScriptError::TooManyCommands {
+                            limit: MAX_COMMANDS,
+                            line: *line,
+                        });
+                    }
+                    commands.push(*command);
+                }
+                Statement::Repeat(times, body) => {
+                    for _ in 0..*times {
+                        let before = commands.len();
+                        self.expand_into(body, calls, commands)?;
+                        // An empty body stays empty, however often it repeats.
+                        if commands.len() == before {
+                            break;
+                        }
+                    }
+                }
+                Statement::Call { name, line } => {
+                    let body = self.macros.get(name).ok_or(ScriptError::UnknownMacro {
+                        name: name.clone(),
+                        line: *line,
+                    })?;
+                    if calls.contains(name) {
+                        return Err(ScriptError::RecursiveMacro {
+                            name: name.clone(),
+                            line: *line,
+                        });
+                    }
+                    calls.push(name.clone());
+                    self.expand_into(body, calls, commands)?;
+                    calls.pop();
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+pub fn read_script(read: &str) -> Result<Vec<Command>, ScriptError> {
+    read.parse::<Script>()?.expand()
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    fn example() -> Vec<Command> {
+        read_commands(&input::read_file(&input::Key::example(2))).unwrap()
+    }
+
     #[test]
     fn challenge1() {
-        let steps = vec![
-            "forward 5",
-            "down 5",
-            "forward 8",
-            "up 3",
-            "down 8",
-            "forward 2",
-        ];
-        let (horiz, depth) = plan_course_with_aim(&steps, false);
-        assert_eq!(horiz, 15);
-        assert_eq!(depth, 10);
+        let position = plan_course(&example(), &Simple, Surface::Allow).unwrap();
+        assert_eq!(position.horiz, 15);
+        assert_eq!(position.depth, 10);
     }
 
     #[test]
     fn challenge2() {
-        let steps = vec![
-            "forward 5",
-            "down 5",
-            "forward 8",
-            "up 3",
-            "down 8",
-            "forward 2",
-        ];
-        let (horiz, depth) = plan_course_with_aim(&steps, true);
-        assert_eq!(horiz, 15);
-        assert_eq!(depth, 60);
+        let position = plan_course(&example(), &Aim, Surface::Allow).unwrap();
+        assert_eq!(position.horiz, 15);
+        assert_eq!(position.depth, 60);
+    }
+
+    #[test]
+    fn parse_works() {
+        assert_eq!("forward 5".parse(), Ok(Command::Forward(5)));
+        assert_eq!(example()[3], Command::Up(3));
+        let error = |line, token: &str, kind| ParseError {
+            line,
+            token: token.to_string(),
+            kind,
+        };
+        assert_eq!(
+            read_commands("down 2\nfoward 5\n").unwrap_err(),
+            error(2, "foward", ParseErrorKind::UnknownCommand)
+        );
+        assert_eq!(
+            read_commands("up -3").unwrap_err(),
+            error(1, "-3", ParseErrorKind::InvalidAmount)
+        );
+        assert_eq!(
+            read_commands("up 3\n\ndown").unwrap_err(),
+            error(2, "", ParseErrorKind::EmptyLine)
+        );
+        assert_eq!(
+            read_commands("up 3\ndown 1\n  \n").unwrap_err().to_string(),
+            "line 3: empty line"
+        );
+        assert_eq!(
+            "down".parse::<Command>().unwrap_err(),
+            error(1, "down", ParseErrorKind::MissingAmount)
+        );
+        assert_eq!(
+            "down 1 2".parse::<Command>().unwrap_err(),
+            error(1, "2", ParseErrorKind::TrailingToken)
+        );
+        assert_eq!(
+            read_commands("forward 1\nsideways 2")
+                .unwrap_err()
+                .to_string(),
+            "line 2: unknown command \"sideways\""
+        );
+    }
+
+    #[test]
+    fn surface_works() {
+        let commands = read_commands("down 1\nup 3\nforward 2\ndown 4\nforward 1").unwrap();
+        assert_eq!(
+            plan_course(&commands, &Aim, Surface::Allow),
+            Ok(Position {
+                horiz: 3,
+                depth: -2,
+                aim: 2
+            })
+        );
+        assert_eq!(
+            plan_course(&commands, &Aim, Surface::Clamp),
+            Ok(Position {
+                horiz: 3,
+                depth: 2,
+                aim: 2
+            })
+        );
+        assert_eq!(
+            plan_course(&commands, &Aim, Surface::Error),
+            Err(NavigationError::AboveSurface {
+                step: 3,
+                command: Command::Forward(2),
+                depth: -4
+            })
+        );
+        assert_eq!(
+            plan_course(&commands, &Simple, Surface::Allow)
+                .unwrap()
+                .depth,
+            2
+        );
+        assert_eq!(
+            plan_course(&commands, &Simple, Surface::Clamp)
+                .unwrap()
+                .depth,
+            4
+        );
+        assert_eq!("clamp".parse(), Ok(Surface::Clamp));
+        assert!("ignore".parse::<Surface>().is_err());
+        assert_eq!(
+            plan_course(&commands, &Simple, Surface::Error)
+                .unwrap_err()
+                .to_string(),
+            "command 2 (up 3) takes the submarine above the surface to depth -2"
+        );
+    }
+
+    #[test]
+    fn overflow_is_reported() {
+        let commands = vec![Command::Down(1), Command::Forward(usize::MAX)];
+        assert_eq!(
+            plan_course(&commands, &Aim, Surface::Allow),
+            Err(NavigationError::Overflow {
+                step: 2,
+                command: Command::Forward(usize::MAX)
+            })
+        );
+        let commands = vec![Command::Down(1 << 40), Command::Forward(1 << 40)];
+        assert!(plan_course(&commands, &Aim, Surface::Allow).is_err());
+        assert!(plan_course(&commands, &Simple, Surface::Allow).is_ok());
+    }
+
+    #[test]
+    fn product_overflow_is_reported() {
+        let key = input::Key::new(input::YEAR, 2, "product-overflow");
+        input::provide_inline(&key, "forward 4000000000\ndown 4000000000\n");
+        assert_eq!(
+            super::challenge1(&key),
+            "Navigation failed: horizontal 4000000000 * depth 4000000000 overflows the answer"
+        );
+        assert_eq!(
+            super::challenge2(&key),
+            "Horizontal 4000000000 * depth 0 = 0"
+        );
+    }
+
+    /// A model outside the built-ins, with a state of its own: a current
+    /// pushes the submarine one unit down for every unit it moves forward,
+    /// and the state keeps how far it has drifted.
+    struct Current;
+
+    #[derive(Debug, Clone, Default, PartialEq, Eq)]
+    struct Drifting {
+        position: Position,
+        drift: i64,
+    }
+
+    impl NavigationState for Drifting {
+        fn horiz(&self) -> i64 {
+            self.position.horiz
+        }
+
+        fn depth(&self) -> i64 {
+            self.position.depth
+        }
+
+        fn set_depth(&mut self, depth: i64) {
+            self.position.depth = depth;
+        }
+
+        fn fields(&self) -> Vec<(&'static str, i64)> {
+            let mut fields = self.position.fields();
+            fields.push(("drift", self.drift));
+            fields
+        }
+    }
+
+    impl NavigationModel for Current {
+        type State = Drifting;
+
+        fn name(&self) -> &'static str {
+            "current"
+        }
+
+        fn apply(&self, s: &Drifting, command: Command) -> Option<Drifting> {
+            let position = Simple.apply(&s.position, command)?;
+            let x = match command {
+                Command::Forward(x) => amount(x)?,
+                _ => 0,
+            };
+            Some(Drifting {
+                position: Position {
+                    depth: position.depth.checked_add(x)?,
+                    ..position
+                },
+                drift: s.drift.checked_add(x)?,
+            })
+        }
+    }
+
+    #[test]
+    fn models_work() {
+        assert_eq!(
+            models().iter().map(|m| m.name()).collect::<Vec<_>>(),
+            vec!["simple", "aim"]
+        );
+        assert_eq!(model("aim").unwrap().name(), "aim");
+        assert!(model("yaw").is_none());
+        let state = plan_course(&example(), &Current, Surface::Error).unwrap();
+        assert_eq!((state.horiz(), state.depth(), state.drift), (15, 25, 15));
+        assert_eq!(
+            Current.describe(&example(), Surface::Error),
+            "Horizontal 15 * depth 25 = 375"
+        );
+        let (trace, _) = trace(&example()[..1], &Current, Surface::Allow);
+        assert_eq!(
+            trace.to_csv(),
+            "step,command,horiz,depth,aim,drift\n0,,0,0,0,0\n1,forward 5,5,5,0,5\n"
+        );
+    }
+
+    #[test]
+    fn trace_works() {
+        let (trace, result) = trace(&example(), &Aim, Surface::Allow);
+        assert_eq!(result.unwrap().depth, 60);
+        assert_eq!(trace.steps.len(), 7);
+        assert_eq!(
+            trace.to_csv(),
+            [
+                "step,command,horiz,depth,aim",
+                "0,,0,0,0",
+                "1,forward 5,5,0,0",
+                "2,down 5,5,0,5",
+                "3,forward 8,13,40,5",
+                "4,up 3,13,40,2",
+                "5,down 8,13,40,10",
+                "6,forward 2,15,60,10",
+                "",
+            ]
+            .join("\n")
+        );
+        let svg = trace.to_svg();
+        assert!(svg.starts_with("<svg xmlns=\"http://www.w3.org/2000/svg\" viewBox=\"0 0 15 60\""));
+        assert!(svg.contains("points=\"0,0 5,0 5,0 13,40 13,40 13,40 15,60\""));
+    }
+
+    #[test]
+    fn trace_stops_at_failure() {
+        let commands = read_commands("forward 1\nup 2\nforward 3").unwrap();
+        let (trace, result) = trace(&commands, &Simple, Surface::Error);
+        assert!(result.is_err());
+        assert_eq!(trace.steps.len(), 2);
+        let (trace, _) = super::trace(&commands, &Simple, Surface::Allow);
+        assert!(trace.to_svg().contains("viewBox=\"0 -2 4 2\""));
+    }
+
+    #[test]
+    fn script_works() {
+        let plain = input::read_file(&input::Key::example(2));
+        assert_eq!(read_script(&plain), Ok(example()));
+        let script = "
+            # the example course, folded up
+            macro dive { down 5 forward 8 }
+            forward 5
+            dive
+            up 3
+            repeat 2 { down 4 }  # twice
+            forward 2
+        ";
+        let folded = read_script(script).unwrap();
+        assert_eq!(folded.len(), 7);
+        assert_eq!(
+            plan_course(&folded, &Simple, Surface::Error),
+            plan_course(&example(), &Simple, Surface::Error)
+        );
+        assert_eq!(
+            plan_course(&folded, &Aim, Surface::Error),
+            plan_course(&example(), &Aim, Surface::Error)
+        );
+        let nested = "macro zigzag { repeat 2 { down 1 up 1 } }\nrepeat 3 { zigzag forward 1 }";
+        let commands = read_script(nested).unwrap();
+        assert_eq!(commands.len(), 15);
+        assert_eq!(commands[4], Command::Forward(1));
+        assert_eq!(read_script(""), Ok(vec![]));
+    }
+
+    #[test]
+    fn script_errors() {
+        let err = |script: &str| read_script(script).unwrap_err();
+        assert_eq!(
+            err("forward 1\nsink"),
+            ScriptError::UnknownMacro {
+                name: "sink".to_string(),
+                line: 2
+            }
+        );
+        assert_eq!(
+            err("macro a { b }\nmacro b { down 1 a }\n\na"),
+            ScriptError::RecursiveMacro {
+                name: "a".to_string(),
+                line: 2
+            }
+        );
+        assert_eq!(
+            err("macro a { up 1 }\nmacro a { up 2 }"),
+            ScriptError::DuplicateMacro {
+                name: "a".to_string(),
+                line: 2
+            }
+        );
+        assert_eq!(
+            err("down 99999999999999999999999"),
+            ScriptError::InvalidAmount {
+                token: "99999999999999999999999".to_string(),
+                line: 1
+            }
+        );
+        assert_eq!(
+            err("down 1\nrepeat 1000 {\n  repeat 1000 { up 1 }\n  forward 1\n}"),
+            ScriptError::TooManyCommands {
+                limit: MAX_COMMANDS,
+                line: 3
+            }
+        );
+        assert_eq!(
+            err("repeat 99999999999 { repeat 99999999999 { } up 1 }").to_string(),
+            format!(
+                "line 1: script expands to more than {} commands",
+                MAX_COMMANDS
+            )
+        );
+        assert!(matches!(err("forward\n5"), ScriptError::Syntax(_)));
+        assert!(matches!(err("repeat 2 { down 1"), ScriptError::Syntax(_)));
+        assert!(matches!(err("forward -1"), ScriptError::Syntax(_)));
+        assert!(matches!(err("repeat { up 1 }"), ScriptError::Syntax(_)));
+        assert!(matches!(err("macro up { down 1 }"), ScriptError::Syntax(_)));
+        assert!(matches!(
+            err("repeat 2 { macro a { up 1 } }"),
+            ScriptError::Syntax(_)
+        ));
     }
 }
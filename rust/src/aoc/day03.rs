@@ -1,28 +1,33 @@
 use crate::aoc::input;
+use crate::aoc::schema::Schema;
 
-pub fn main(day: usize) {
-    println!("...1: {}", challenge1(day));
-    println!("...2: {}", challenge2(day));
+pub fn main(key: &input::Key) {
+    println!("...1: {}", challenge1(key));
+    println!("...2: {}", challenge2(key));
 }
 
-pub fn challenge1(day: usize) -> String {
-    let read = input::read_file(day, 1);
-    let lines = read.lines().collect();
+pub fn challenge1(key: &input::Key) -> String {
+    let read = input::read_file(key);
+    let lines = read.lines().collect::<Vec<_>>();
     let (gamma, epsilon) = calc_power_consumption(&lines);
-    return format!(
+    format!(
         "Gamma {} * Epsilon {} = {:}",
         gamma,
         epsilon,
         isize::from_str_radix(gamma.as_str(), 2).unwrap()
             * isize::from_str_radix(epsilon.as_str(), 2).unwrap()
-    );
+    )
 }
 
-pub fn challenge2(day: usize) -> String {
-    let read = input::read_file(day, 1);
-    let lines = read.lines().collect();
+pub fn challenge2(key: &input::Key) -> String {
+    let read = input::read_file(key);
+    let lines = read.lines().collect::<Vec<_>>();
     let (oxy, co2) = calc_life_support_rating(&lines);
-    return format!("Oxygen {} * CO2 {} = {:}", oxy, co2, oxy * co2);
+    format!("Oxygen {} * CO2 {} = {:}", oxy, co2, oxy * co2)
+}
+
+pub fn schema() -> Schema {
+    Schema::grid("01")
 }
 
 fn calc_power_consumption(measurements: &Vec<&str>) -> (String, String) {
@@ -61,8 +66,8 @@ fn transpose<T>(v: Vec<Vec<T>>) -> Vec<Vec<T>> {
         .collect()
 }
 
-fn reduce(vec: &Vec<&str>, matcher: fn(ones: usize, zeros: usize) -> char) -> i64 {
-    let mut vec = vec.clone();
+fn reduce(vec: &[&str], matcher: fn(ones: usize, zeros: usize) -> char) -> i64 {
+    let mut vec = vec.to_vec();
     let mut column: usize = 0;
     while vec.len() > 1 {
         let comp = transpose(vec.iter().map(|l| l.chars().collect()).collect())
@@ -82,18 +87,14 @@ fn reduce(vec: &Vec<&str>, matcher: fn(ones: usize, zeros: usize) -> char) -> i6
         .unwrap()
 }
 
-fn calc_life_support_rating(measurements: &Vec<&str>) -> (i64, i64) {
-    let oxy = reduce(&measurements.clone(), |ones, zeros: usize| {
-        match ones >= zeros {
-            true => '1',
-            _ => '0',
-        }
+fn calc_life_support_rating(measurements: &[&str]) -> (i64, i64) {
+    let oxy = reduce(measurements, |ones, zeros: usize| match ones >= zeros {
+        true => '1',
+        _ => '0',
     });
-    let co2 = reduce(&measurements.clone(), |ones, zeros: usize| {
-        match ones < zeros {
-            true => '1',
-            _ => '0',
-        }
+    let co2 = reduce(measurements, |ones, zeros: usize| match ones < zeros {
+        true => '1',
+        _ => '0',
     });
     (oxy, co2)
 }
@@ -1,24 +1,49 @@
 use crate::aoc::input;
+use crate::aoc::schema::{Schema, Section, Token};
 
-pub fn main(day: usize) {
-    println!("...1: {}", challenge1(day));
-    println!("...2: {}", challenge2(day));
+pub fn main(key: &input::Key) {
+    println!("...1: {}", challenge1(key));
+    println!("...2: {}", challenge2(key));
 }
 
-pub fn challenge1(day: usize) -> String {
-    let read = input::read_file(day, 1);
+pub fn challenge1(key: &input::Key) -> String {
+    let read = input::read_file(key);
     let numbers = read_drawn_numbers(read.lines().next().unwrap().to_string());
     let mut boards = read_boards(read.lines().skip(2), 5);
     let (score, number) = play(&mut boards, numbers);
-    return format!("Score {} * Number {} = {:}", score, number, score * number);
+    format!("Score {} * Number {} = {:}", score, number, score * number)
 }
 
-pub fn challenge2(day: usize) -> String {
-    let read = input::read_file(day, 1);
+pub fn challenge2(key: &input::Key) -> String {
+    let read = input::read_file(key);
     let numbers = read_drawn_numbers(read.lines().next().unwrap().to_string());
     let mut boards = read_boards(read.lines().skip(2), 5);
     let (score, number) = play_last(&mut boards, numbers);
-    return format!("Score {} * Number {} = {:}", score, number, score * number);
+    format!("Score {} * Number {} = {:}", score, number, score * number)
+}
+
+pub fn schema() -> Schema {
+    Schema::sections(vec![
+        Section::new(
+            "drawn numbers",
+            vec![Token::list(
+                Token::Number(0, 99),
+                Token::Literal(","),
+                1,
+                usize::MAX,
+            )],
+        )
+        .lines(1, 1),
+        Section::new(
+            "board",
+            vec![
+                Token::optional(Token::Spaces),
+                Token::list(Token::Number(0, 99), Token::Spaces, 5, 5),
+            ],
+        )
+        .lines(5, 5)
+        .repeated(),
+    ])
 }
 
 pub fn read_drawn_numbers(file: String) -> Vec<usize> {
@@ -70,19 +95,14 @@ impl Board {
             .unwrap()
     }
     pub fn row(&self, index: usize) -> Vec<isize> {
-        self.grid
-            .windows(5)
-            .nth(index * 5)
-            .unwrap()
-            .try_into()
-            .unwrap()
+        self.grid.windows(5).nth(index * 5).unwrap().into()
     }
     pub fn col(&self, index: usize) -> Vec<isize> {
         self.grid
             .iter()
             .skip(index)
             .step_by(5)
-            .map(|e| *e)
+            .copied()
             .collect::<Vec<_>>()
     }
 }
@@ -92,7 +112,7 @@ pub fn read_boards(lines: std::iter::Skip<std::str::Lines<'_>>, gridsize: usize)
         .fold(String::new(), |a, l| a + l + "\n")
         .split("\n\n")
         .map(|b| Board {
-            gridsize: gridsize,
+            gridsize,
             grid: b
                 .to_string()
                 .split_ascii_whitespace()
@@ -102,17 +122,17 @@ pub fn read_boards(lines: std::iter::Skip<std::str::Lines<'_>>, gridsize: usize)
         .collect::<Vec<_>>()
 }
 
-pub fn play_boards(boards: &mut Vec<Board>, number: isize) -> Option<&Board> {
+pub fn play_boards(boards: &mut [Board], number: isize) -> Option<&Board> {
     let mut won: Option<&Board> = None;
     for b in boards.iter_mut() {
         if b.play(number) {
             won = Some(b);
         }
     }
-    return won;
+    won
 }
 
-pub fn play(boards: &mut Vec<Board>, numbers: Vec<usize>) -> (usize, usize) {
+pub fn play(boards: &mut [Board], numbers: Vec<usize>) -> (usize, usize) {
     for n in numbers.iter() {
         if let Some(b) = play_boards(boards, TryInto::<isize>::try_into(*n).unwrap()) {
             return (b.score(), *n);
@@ -140,7 +160,7 @@ mod tests {
 
     #[test]
     fn challenge1() {
-        let read = input::read_file(4, 0);
+        let read = input::read_file(&input::Key::example(4));
         let numbers = read_drawn_numbers(read.lines().next().unwrap().to_string());
         let mut boards = read_boards(read.lines().skip(2), 5);
         let (score, number) = play(&mut boards, numbers);
@@ -150,7 +170,7 @@ mod tests {
 
     #[test]
     fn challenge2() {
-        let read = input::read_file(4, 0);
+        let read = input::read_file(&input::Key::example(4));
         let numbers = read_drawn_numbers(read.lines().next().unwrap().to_string());
         let mut boards = read_boards(read.lines().skip(2), 5);
         println!("{:?}", boards);
@@ -161,7 +181,7 @@ mod tests {
 
     #[test]
     fn board_row() {
-        let read = input::read_file(4, 0);
+        let read = input::read_file(&input::Key::example(4));
         let boards = read_boards(read.lines().skip(2), 5);
         assert_eq!(boards[0].row(0), vec![22, 13, 17, 11, 0]);
         assert_eq!(boards[0].row(1), vec![8, 2, 23, 4, 24]);
@@ -170,7 +190,7 @@ mod tests {
 
     #[test]
     fn board_col() {
-        let read = input::read_file(4, 0);
+        let read = input::read_file(&input::Key::example(4));
         let boards = read_boards(read.lines().skip(2), 5);
         assert_eq!(boards[0].col(0), vec![22, 8, 21, 6, 1]);
         assert_eq!(boards[0].col(1), vec![13, 2, 9, 10, 12]);
@@ -1,39 +1,56 @@
 use crate::aoc::input;
+use crate::aoc::schema::{Schema, Token};
 
 use recap::Recap;
 use serde::Deserialize;
 
-pub fn main(day: usize) {
-    println!("...1: {}", challenge1(day));
-    println!("...2: {}", challenge2(day));
+pub fn main(key: &input::Key) {
+    println!("...1: {}", challenge1(key));
+    println!("...2: {}", challenge2(key));
 }
 
-pub fn challenge1(day: usize) -> String {
-    let read = input::read_file(day, 1);
-    let lines = read_lines(read);
+pub fn challenge1(key: &input::Key) -> String {
+    let lines = match read_lines(input::read_file(key)) {
+        Ok(lines) => lines,
+        Err(err) => return format!("Invalid vent lines: {}", err),
+    };
     let mut field = Field::new(1000);
     lines
         .iter()
         .filter(|l| l.horiz_vert())
         .for_each(|l| field.add(l));
-    return format!(
+    format!(
         "Count of fields crossed by 2 or more vent lines: {}",
         field.overlaps()
-    );
+    )
 }
 
-pub fn challenge2(day: usize) -> String {
-    let read = input::read_file(day, 1);
-    let lines = read_lines(read);
+pub fn challenge2(key: &input::Key) -> String {
+    let lines = match read_lines(input::read_file(key)) {
+        Ok(lines) => lines,
+        Err(err) => return format!("Invalid vent lines: {}", err),
+    };
     let mut field = Field::new(1000);
     lines
         .iter()
         .filter(|l| l.horiz_vert() || l.diag())
         .for_each(|l| field.add(l));
-    return format!(
+    format!(
         "Count of fields crossed by 2 or more vent lines: {}",
         field.overlaps()
-    );
+    )
+}
+
+pub fn schema() -> Schema {
+    Schema::lines(vec![
+        Token::Number(0, 999),
+        Token::Literal(","),
+        Token::Number(0, 999),
+        Token::Literal(" -> "),
+        Token::Number(0, 999),
+        Token::Literal(","),
+        Token::Number(0, 999),
+    ])
 }
 
 pub struct Field {
@@ -62,7 +79,7 @@ impl Field {
                 .iter()
                 .cycle()
                 .take(y_range.len())
-                .map(|v| *v)
+                .copied()
                 .collect();
         }
         if y_range.len() < x_range.len() {
@@ -70,7 +87,7 @@ impl Field {
                 .iter()
                 .cycle()
                 .take(x_range.len())
-                .map(|v| *v)
+                .copied()
                 .collect();
         }
         for (x, y) in x_range.iter().zip(y_range) {
@@ -91,9 +108,9 @@ impl std::fmt::Display for Field {
             for y in x {
                 write!(f, "{}", y).expect("");
             }
-            write!(f, "\n").expect("");
+            writeln!(f).expect("");
         }
-        write!(f, "\n")
+        writeln!(f)
     }
 }
 
@@ -122,8 +139,15 @@ impl Line {
     }
 }
 
-pub fn read_lines(lines: String) -> Vec<Line> {
-    lines.lines().filter_map(|l| l.parse().ok()).collect()
+pub fn read_lines(lines: String) -> Result<Vec<Line>, String> {
+    lines
+        .lines()
+        .enumerate()
+        .map(|(i, l)| {
+            l.parse()
+                .map_err(|_| format!("line {}: invalid vent line {:?}", i + 1, l))
+        })
+        .collect()
 }
 
 #[cfg(test)]
@@ -132,8 +156,8 @@ mod tests {
 
     #[test]
     fn challenge1() {
-        let read = input::read_file(5, 0);
-        let lines = read_lines(read);
+        let read = input::read_file(&input::Key::example(5));
+        let lines = read_lines(read).unwrap();
         assert_eq!(lines[0].x1, 0);
         assert_eq!(lines[0].y1, 9);
         let mut field = Field::new(10);
@@ -146,8 +170,8 @@ mod tests {
 
     #[test]
     fn challenge2() {
-        let read = input::read_file(5, 0);
-        let lines = read_lines(read);
+        let read = input::read_file(&input::Key::example(5));
+        let lines = read_lines(read).unwrap();
         assert_eq!(lines[0].x1, 0);
         assert_eq!(lines[0].y1, 9);
         let mut field = Field::new(10);
@@ -163,7 +187,19 @@ mod tests {
         let lines = vec!["1,1 -> 3,3", "3,3 -> 1,1", "9,7 -> 7,9", "4,3 -> 6,5"];
         for l in lines {
             let line: Line = l.parse().unwrap();
-            assert_eq!(line.diag(), true, "{}", l);
+            assert!(line.diag(), "{}", l);
         }
     }
+
+    #[test]
+    fn invalid_lines_are_reported() {
+        let err = read_lines("0,9 -> 5,9\n8,0 => 0,8\n".to_string()).unwrap_err();
+        assert_eq!(err, "line 2: invalid vent line \"8,0 => 0,8\"");
+        let key = input::Key::new(input::YEAR, 5, "invalid");
+        input::provide_inline(&key, "0,9 -> 5,9\n8,0\n");
+        assert_eq!(
+            super::challenge1(&key),
+            "Invalid vent lines: line 2: invalid vent line \"8,0\""
+        );
+    }
 }
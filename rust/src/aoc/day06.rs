@@ -1,14 +1,15 @@
 use crate::aoc::input;
+use crate::aoc::schema::{Schema, Section, Token};
 
 use std::collections::HashMap;
 
-pub fn main(day: usize) {
-    println!("...1: {}", challenge1(day));
-    println!("...2: {}", challenge2(day));
+pub fn main(key: &input::Key) {
+    println!("...1: {}", challenge1(key));
+    println!("...2: {}", challenge2(key));
 }
 
-pub fn challenge1(day: usize) -> String {
-    let mut fish = input::line_as_usize(day, 1).unwrap();
+pub fn challenge1(key: &input::Key) -> String {
+    let mut fish = input::line_as_usize(key).unwrap();
     format!(
         "Count of lanternfish after {} days: {}",
         80,
@@ -16,8 +17,8 @@ pub fn challenge1(day: usize) -> String {
     )
 }
 
-pub fn challenge2(day: usize) -> String {
-    let mut fish = input::line_as_usize(day, 1).unwrap();
+pub fn challenge2(key: &input::Key) -> String {
+    let mut fish = input::line_as_usize(key).unwrap();
     format!(
         "Count of lanternfish after {} days: {}",
         256,
@@ -25,6 +26,19 @@ pub fn challenge2(day: usize) -> String {
     )
 }
 
+pub fn schema() -> Schema {
+    Schema::sections(vec![Section::new(
+        "timers",
+        vec![Token::list(
+            Token::Number(0, 8),
+            Token::Literal(","),
+            1,
+            usize::MAX,
+        )],
+    )
+    .lines(1, 1)])
+}
+
 pub fn simulate(fish: &mut Vec<usize>, days: usize) -> usize {
     let mut fish_map = HashMap::<usize, usize>::new();
     for f in fish {
@@ -42,7 +56,7 @@ pub fn simulate(fish: &mut Vec<usize>, days: usize) -> usize {
         }
         fish_map = new_fish_map;
     }
-    fish_map.iter().map(|(_k, v)| *v).sum()
+    fish_map.values().copied().sum()
 }
 
 #[cfg(test)]
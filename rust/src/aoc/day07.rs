@@ -1,26 +1,37 @@
 use crate::aoc::input;
+use crate::aoc::schema::{Schema, Section, Token};
 
-pub fn main(day: usize) {
-    println!("...1: {}", challenge1(day));
-    println!("...2: {}", challenge2(day));
+pub fn main(key: &input::Key) {
+    println!("...1: {}", challenge1(key));
+    println!("...2: {}", challenge2(key));
 }
 
-pub fn challenge1(day: usize) -> String {
-    let mut positions = input::line_as_usize(day, 1).unwrap();
+pub fn challenge1(key: &input::Key) -> String {
+    let mut positions = input::line_as_usize(key).unwrap();
     let (on, fuel) = calc_alignment(&mut positions, |dist| dist);
     format!("Aligning on {} uses the least amount of fuel: {}", on, fuel)
 }
 
-pub fn challenge2(day: usize) -> String {
-    let mut positions = input::line_as_usize(day, 1).unwrap();
+pub fn challenge2(key: &input::Key) -> String {
+    let mut positions = input::line_as_usize(key).unwrap();
     let (on, fuel) = calc_alignment(&mut positions, |dist| dist * (dist + 1) / 2);
     format!("Aligning on {} uses the least amount of fuel: {}", on, fuel)
 }
 
-pub fn calc_alignment(
-    positions: &mut Vec<usize>,
-    dist: fn(dist: isize) -> isize,
-) -> (usize, usize) {
+pub fn schema() -> Schema {
+    Schema::sections(vec![Section::new(
+        "positions",
+        vec![Token::list(
+            Token::Number(0, i64::MAX),
+            Token::Literal(","),
+            1,
+            usize::MAX,
+        )],
+    )
+    .lines(1, 1)])
+}
+
+pub fn calc_alignment(positions: &mut [usize], dist: fn(dist: isize) -> isize) -> (usize, usize) {
     let min = positions.iter().min().unwrap();
     let max = positions.iter().max().unwrap();
     let mut align = 0;
@@ -53,7 +64,7 @@ mod tests {
     #[test]
     fn challenge2() {
         let positions = vec![16, 1, 2, 0, 4, 2, 7, 1, 2, 14];
-        let (on, fuel) = calc_alignment(&mut positions.clone(), |dist| (dist * (dist + 1) / 2));
+        let (on, fuel) = calc_alignment(&mut positions.clone(), |dist| dist * (dist + 1) / 2);
         assert_eq!(on, 5);
         assert_eq!(fuel, 168);
     }
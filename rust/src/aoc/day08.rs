@@ -1,18 +1,37 @@
 use crate::aoc::input;
+use crate::aoc::schema::{Schema, Token};
 
 use bimap::BiMap;
 
 use itertools::Itertools;
 
-pub fn main(day: usize) {
-    println!("...1: {}", challenge1(day));
-    println!("...2: {}", challenge2(day));
+pub fn main(key: &input::Key) {
+    println!("...1: {}", challenge1(key));
+    println!("...2: {}", challenge2(key));
 }
 
-pub fn challenge1(day: usize) -> String {
-    let read = input::read_file(day, 1);
-    let outputs = read
-        .lines()
+pub fn challenge1(key: &input::Key) -> String {
+    let read = input::read_file(key);
+    let count = count_unique_combinations(read_outputs(&read));
+    format!("The digits 1, 4, 7, or 8 appear {} times", count)
+}
+
+pub fn challenge2(key: &input::Key) -> String {
+    let read = input::read_file(key);
+    let sum = sum_decoded_outputs(&read);
+    format!("The sum of all decoded numbers is {}", sum)
+}
+
+pub fn schema() -> Schema {
+    Schema::lines(vec![
+        Token::list(Token::Chars("abcdefg"), Token::Literal(" "), 10, 10),
+        Token::Literal(" | "),
+        Token::list(Token::Chars("abcdefg"), Token::Literal(" "), 4, 4),
+    ])
+}
+
+pub fn read_outputs(read: &str) -> Vec<Vec<&str>> {
+    read.lines()
         .map(|l| {
             l.split(" | ")
                 .last()
@@ -20,15 +39,11 @@ pub fn challenge1(day: usize) -> String {
                 .split(" ")
                 .collect::<Vec<_>>()
         })
-        .collect();
-    let count = count_unique_combinations(outputs);
-    format!("The digits 1, 4, 7, or 8 appear {} times", count)
+        .collect()
 }
 
-pub fn challenge2(day: usize) -> String {
-    let read = input::read_file(day, 1);
-    let sum: usize = read
-        .lines()
+pub fn sum_decoded_outputs(read: &str) -> usize {
+    read.lines()
         .map(|l| {
             let l = l
                 .split(" | ")
@@ -38,37 +53,23 @@ pub fn challenge2(day: usize) -> String {
             let mut number = 0;
             digits.reverse();
             let base: usize = 10;
-            for i in 0..digits.len() {
-                number += digits[i] * (base.pow(i as u32))
+            for (i, digit) in digits.iter().enumerate() {
+                number += digit * (base.pow(i as u32))
             }
             number
         })
-        .sum();
-    format!("The sum of all decoded numbers is {}", sum)
+        .sum()
 }
 
 pub fn count_unique_combinations(outputs: Vec<Vec<&str>>) -> usize {
     outputs
         .iter()
-        .map(|l| {
-            l.iter()
-                .filter_map(|o| match is_unique_combination(o) {
-                    true => Some(o),
-                    _ => None,
-                })
-                .count()
-        })
+        .map(|l| l.iter().filter(|o| is_unique_combination(o)).count())
         .sum()
 }
 
 pub fn is_unique_combination(output: &str) -> bool {
-    match output.len() {
-        2 => true,
-        3 => true,
-        4 => true,
-        7 => true,
-        _ => false,
-    }
+    matches!(output.len(), 2 | 3 | 4 | 7)
 }
 
 pub fn decode_output(input: Vec<&str>, output: Vec<&str>) -> Vec<usize> {
@@ -169,54 +170,21 @@ mod tests {
 
     #[test]
     fn challenge1() {
-        let read = input::read_file(8, 0);
-        let outputs = read
-            .lines()
-            .map(|l| {
-                l.split(" | ")
-                    .last()
-                    .unwrap()
-                    .split(" ")
-                    .collect::<Vec<_>>()
-            })
-            .collect();
-        let count = count_unique_combinations(outputs);
+        let read = input::read_file(&input::Key::example(8));
+        let count = count_unique_combinations(read_outputs(&read));
         assert_eq!(count, 26);
     }
 
     #[test]
     fn challenge2() {
-        let read = input::read_file(8, 0);
-        let sum: usize = read
-            .lines()
-            .map(|l| {
-                let l = l
-                    .split(" | ")
-                    .map(|v| v.split(" ").collect::<Vec<&str>>())
-                    .collect::<Vec<Vec<&str>>>();
-                let digits = &mut decode_output(l[0].clone(), l[1].clone());
-                let mut number = 0;
-                digits.reverse();
-                let base: usize = 10;
-                for i in 0..digits.len() {
-                    number += digits[i] * (base.pow(i as u32))
-                }
-                number
-            })
-            .sum();
-        assert_eq!(sum, 61229);
+        let read = input::read_file(&input::Key::example(8));
+        assert_eq!(sum_decoded_outputs(&read), 61229);
     }
 
     #[test]
     fn count_unique_combinations_works() {
-        let outputs = vec!["ab", "abc", "abcd", "abcde", "abcdef", "abcdefg"];
-        let count = outputs
-            .iter()
-            .filter_map(|o| match is_unique_combination(o) {
-                true => Some(o),
-                _ => None,
-            })
-            .count();
+        let outputs = ["ab", "abc", "abcd", "abcde", "abcdef", "abcdefg"];
+        let count = outputs.iter().filter(|o| is_unique_combination(o)).count();
         assert_eq!(count, 4);
     }
 
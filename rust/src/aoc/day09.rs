@@ -1,14 +1,15 @@
 use crate::aoc::input;
+use crate::aoc::schema::Schema;
 
 use std::collections::HashMap;
 
-pub fn main(day: usize) {
-    println!("...1: {}", challenge1(day));
-    println!("...2: {}", challenge2(day));
+pub fn main(key: &input::Key) {
+    println!("...1: {}", challenge1(key));
+    println!("...2: {}", challenge2(key));
 }
 
-pub fn challenge1(day: usize) -> String {
-    let read = input::read_file(day, 1);
+pub fn challenge1(key: &input::Key) -> String {
+    let read = input::read_file(key);
     let h = read_heightmap(read);
     format!(
         "Sum of the risk levels of all low points: {}",
@@ -16,8 +17,8 @@ pub fn challenge1(day: usize) -> String {
     )
 }
 
-pub fn challenge2(day: usize) -> String {
-    let read = input::read_file(day, 1);
+pub fn challenge2(key: &input::Key) -> String {
+    let read = input::read_file(key);
     let mut h = read_heightmap(read);
     h.flood_fill();
     format!(
@@ -26,6 +27,10 @@ pub fn challenge2(day: usize) -> String {
     )
 }
 
+pub fn schema() -> Schema {
+    Schema::grid("0123456789")
+}
+
 #[derive(Debug, Clone)]
 pub struct Heightmap {
     grid: Vec<Vec<usize>>,
@@ -86,37 +91,32 @@ impl Heightmap {
         }
     }
     fn flood_step(&mut self, row: usize, col: usize, color: usize) {
-        match self.field_mut(row, col) {
-            Some(val) => match val {
-                0..=8 => {
-                    *val = color;
-                    if row > 0 {
-                        self.flood_step(row - 1, col, color);
-                    }
-                    self.flood_step(row + 1, col, color);
-                    if col > 0 {
-                        self.flood_step(row, col - 1, color);
-                    }
-                    self.flood_step(row, col + 1, color);
+        if let Some(val) = self.field_mut(row, col) {
+            if let 0..=8 = val {
+                *val = color;
+                if row > 0 {
+                    self.flood_step(row - 1, col, color);
+                }
+                self.flood_step(row + 1, col, color);
+                if col > 0 {
+                    self.flood_step(row, col - 1, color);
                 }
-                _ => {}
-            },
-            None => {}
+                self.flood_step(row, col + 1, color);
+            }
         }
     }
     fn field_mut(&mut self, row: usize, col: usize) -> Option<&mut usize> {
-        return self.grid.get_mut(row)?.get_mut(col);
+        self.grid.get_mut(row)?.get_mut(col)
     }
     pub fn multiply_largest_basin_sizes(&self) -> usize {
         let mut basins = HashMap::new();
         self.grid
             .iter()
-            .map(|r| r.iter().filter(|&&c| c > 9).collect::<Vec<_>>())
-            .flatten()
+            .flat_map(|r| r.iter().filter(|&&c| c > 9).collect::<Vec<_>>())
             .for_each(|b| {
                 *basins.entry(b).or_insert(0) += 1;
             });
-        let mut basins = basins.into_iter().map(|(_, v)| v).collect::<Vec<_>>();
+        let mut basins = basins.into_values().collect::<Vec<_>>();
         basins.sort_by(|a, b| b.cmp(a));
         let mut product = 1;
         for b in basins.windows(3).nth(0).unwrap() {
@@ -145,7 +145,7 @@ mod tests {
 
     #[test]
     fn challenge1() {
-        let read = input::read_file(9, 0);
+        let read = input::read_file(&input::Key::example(9));
         let h = read_heightmap(read);
         let sum = h.calc_risk_sum();
         assert_eq!(sum, 15);
@@ -153,7 +153,7 @@ mod tests {
 
     #[test]
     fn challenge2() {
-        let read = input::read_file(9, 0);
+        let read = input::read_file(&input::Key::example(9));
         let mut h = read_heightmap(read);
         h.flood_fill();
         assert_eq!(h.multiply_largest_basin_sizes(), 1134);
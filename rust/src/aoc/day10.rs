@@ -1,29 +1,34 @@
 use crate::aoc::input;
+use crate::aoc::schema::{Schema, Token};
 
 extern crate pest;
 use pest::Parser;
 
-pub fn main(day: usize) {
-    println!("...1: {}", challenge1(day));
-    println!("...2: {}", challenge2(day));
+pub fn main(key: &input::Key) {
+    println!("...1: {}", challenge1(key));
+    println!("...2: {}", challenge2(key));
 }
 
-pub fn challenge1(day: usize) -> String {
-    let score: usize = score_illegal(&input::read_file(day, 1));
+pub fn challenge1(key: &input::Key) -> String {
+    let score: usize = score_illegal(&input::read_file(key));
     format!(
         "Total syntax error score for illegal character errors: {}",
         score
     )
 }
 
-pub fn challenge2(day: usize) -> String {
-    let score: usize = score_incomplete(&input::read_file(day, 1));
+pub fn challenge2(key: &input::Key) -> String {
+    let score: usize = score_incomplete(&input::read_file(key));
     format!(
         "Total syntax error score for incomplete character errors: {}",
         score
     )
 }
 
+pub fn schema() -> Schema {
+    Schema::lines(vec![Token::Chars("()[]{}<>")])
+}
+
 #[derive(pest_derive::Parser)]
 #[grammar = "aoc/day10.pest"]
 pub struct NavParser;
@@ -54,7 +59,7 @@ pub fn scoring_illegal(symbol: char) -> usize {
 }
 
 pub fn generate_one(incomplete: &mut String, score: &mut usize) -> (String, usize) {
-    if let Err(err) = NavParser::parse(Rule::line, &incomplete) {
+    if let Err(err) = NavParser::parse(Rule::line, incomplete) {
         if let pest::error::InputLocation::Pos(pos) = err.location {
             if pos >= incomplete.len() {
                 if let pest::error::ErrorVariant::ParsingError {
@@ -90,7 +95,7 @@ pub fn score_incomplete(read: &str) -> usize {
         })
         .collect::<Vec<_>>();
     scores.sort_by(|a, b| b.cmp(a));
-    *scores.iter().nth(scores.len() / 2).unwrap()
+    *scores.get(scores.len() / 2).unwrap()
 }
 
 pub fn scoring_incomplete(symbol: Rule) -> usize {
@@ -119,23 +124,23 @@ mod tests {
 
     #[test]
     fn challenge1() {
-        let score: usize = score_illegal(&input::read_file(10, 0));
+        let score: usize = score_illegal(&input::read_file(&input::Key::example(10)));
         assert_eq!(score, 26397);
     }
 
     #[test]
     fn challenge2() {
-        let score: usize = score_incomplete(&input::read_file(10, 0));
+        let score: usize = score_incomplete(&input::read_file(&input::Key::example(10)));
         assert_eq!(score, 288957);
     }
 
     #[test]
     fn parsing_works() {
-        assert_eq!(NavParser::parse(Rule::line, "{}").is_ok(), true);
-        assert_eq!(NavParser::parse(Rule::line, "{{}}").is_ok(), true);
-        assert_eq!(NavParser::parse(Rule::line, "{}{}").is_ok(), true);
-        assert_eq!(NavParser::parse(Rule::line, "{{{}").is_ok(), false);
-        assert_eq!(NavParser::parse(Rule::line, "[]").is_ok(), true);
-        assert_eq!(NavParser::parse(Rule::line, "[}").is_ok(), false);
+        assert!(NavParser::parse(Rule::line, "{}").is_ok());
+        assert!(NavParser::parse(Rule::line, "{{}}").is_ok());
+        assert!(NavParser::parse(Rule::line, "{}{}").is_ok());
+        assert!(NavParser::parse(Rule::line, "{{{}").is_err());
+        assert!(NavParser::parse(Rule::line, "[]").is_ok());
+        assert!(NavParser::parse(Rule::line, "[}").is_err());
     }
 }
@@ -1,24 +1,29 @@
 use crate::aoc::input;
+use crate::aoc::schema::Schema;
 
-pub fn main(day: usize) {
-    println!("...1: {}", challenge1(day));
-    println!("...2: {}", challenge2(day));
+pub fn main(key: &input::Key) {
+    println!("...1: {}", challenge1(key));
+    println!("...2: {}", challenge2(key));
 }
 
-pub fn challenge1(day: usize) -> String {
-    let mut grid = read_grid(day, 1);
+pub fn challenge1(key: &input::Key) -> String {
+    let mut grid = read_grid(key);
     let flashes = do_step(&mut grid, 100);
     format!("Total flashes after step 100: {}", flashes)
 }
 
-pub fn challenge2(day: usize) -> String {
-    let mut grid = read_grid(day, 1);
+pub fn challenge2(key: &input::Key) -> String {
+    let mut grid = read_grid(key);
     let steps = do_sync(&mut grid);
     format!("Synced after step: {}", steps)
 }
 
-pub fn read_grid(day: usize, challenge: usize) -> Vec<Vec<usize>> {
-    input::read_file(day, challenge)
+pub fn schema() -> Schema {
+    Schema::grid("0123456789")
+}
+
+pub fn read_grid(key: &input::Key) -> Vec<Vec<usize>> {
+    input::read_file(key)
         .lines()
         .map(|l| {
             l.chars()
@@ -58,11 +63,11 @@ pub fn do_step(grid: &mut Vec<Vec<usize>>, steps: usize) -> usize {
                 flash(grid, r as isize, c as isize);
             }
         }
-        for r in 0..grid.len() {
-            for c in 0..grid[r].len() {
-                if grid[r][c] == 10 {
+        for row in grid.iter_mut() {
+            for octopus in row.iter_mut() {
+                if *octopus == 10 {
                     flashes += 1;
-                    grid[r][c] = 0;
+                    *octopus = 0;
                 }
             }
         }
@@ -89,7 +94,7 @@ mod tests {
 
     #[test]
     fn challenge1() {
-        let mut grid = read_grid(11, 0);
+        let mut grid = read_grid(&input::Key::example(11));
         let mut flashes = do_step(&mut grid, 1);
         assert_eq!(flashes, 0);
         assert_eq!(
@@ -132,7 +137,7 @@ mod tests {
 
     #[test]
     fn challenge2() {
-        let mut grid = read_grid(11, 0);
+        let mut grid = read_grid(&input::Key::example(11));
         let steps = do_sync(&mut grid);
         assert_eq!(steps, 195);
     }
@@ -1,15 +1,16 @@
 use crate::aoc::input;
+use crate::aoc::schema::{Schema, Token, Violation};
 
 use petgraph::prelude::*;
 use std::collections::BTreeMap;
 
-pub fn main(day: usize) {
-    println!("...1: {}", challenge1(day));
-    println!("...2: {}", challenge2(day));
+pub fn main(key: &input::Key) {
+    println!("...1: {}", challenge1(key));
+    println!("...2: {}", challenge2(key));
 }
 
-pub fn challenge1(day: usize) -> String {
-    let read = input::read_file(day, 1);
+pub fn challenge1(key: &input::Key) -> String {
+    let read = input::read_file(key);
     let unique_paths = count_unique_paths(read, lowercase_only_once);
     format!(
         "Total unique paths visiting small caves once: {}",
@@ -17,8 +18,8 @@ pub fn challenge1(day: usize) -> String {
     )
 }
 
-pub fn challenge2(day: usize) -> String {
-    let read = input::read_file(day, 1);
+pub fn challenge2(key: &input::Key) -> String {
+    let read = input::read_file(key);
     let unique_paths = count_unique_paths(read, lowercase_only_one_twice);
     format!(
         "Total unique paths visiting at most one small caves twice: {}",
@@ -26,9 +27,30 @@ pub fn challenge2(day: usize) -> String {
     )
 }
 
+pub fn schema() -> Schema {
+    const CAVE: &str = "abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ";
+    Schema::lines(vec![
+        Token::Chars(CAVE),
+        Token::Literal("-"),
+        Token::Chars(CAVE),
+    ])
+    .check(start_and_end)
+}
+
+fn start_and_end(read: &str) -> Vec<Violation> {
+    ["start", "end"]
+        .iter()
+        .filter(|cave| !read.lines().any(|l| l.split('-').any(|c| c == **cave)))
+        .map(|cave| Violation::new(1, 1, &format!("missing `{}` cave", cave)))
+        .collect()
+}
+
+pub type Exclude =
+    fn(graph: &Graph<&str, i32, Undirected>, path: &[NodeIndex], n: NodeIndex) -> bool;
+
 pub fn lowercase_only_once(
     graph: &Graph<&str, i32, Undirected>,
-    path: &Vec<NodeIndex>,
+    path: &[NodeIndex],
     n: NodeIndex,
 ) -> bool {
     graph[n].to_lowercase().eq(graph[n]) && path.contains(&n)
@@ -36,7 +58,7 @@ pub fn lowercase_only_once(
 
 pub fn lowercase_only_one_twice(
     graph: &Graph<&str, i32, Undirected>,
-    path: &Vec<NodeIndex>,
+    path: &[NodeIndex],
     n: NodeIndex,
 ) -> bool {
     if graph[n].eq("start") || graph[n].eq("end") {
@@ -64,14 +86,11 @@ pub fn lowercase_only_one_twice(
     false
 }
 
-pub fn count_unique_paths(
-    read: String,
-    exclude: fn(graph: &Graph<&str, i32, Undirected>, path: &Vec<NodeIndex>, n: NodeIndex) -> bool,
-) -> usize {
+pub fn count_unique_paths(read: String, exclude: Exclude) -> usize {
     let mut graph = Graph::<&str, i32, Undirected>::new_undirected();
 
     for line in read.lines() {
-        let left_name = line.split("-").nth(0).unwrap();
+        let left_name = line.split("-").next().unwrap();
         let right_name = line.split("-").nth(1).unwrap();
         let left = graph
             .node_indices()
@@ -87,7 +106,7 @@ pub fn count_unique_paths(
     let start = graph.node_indices().find(|i| graph[*i] == "start").unwrap();
     let end = graph.node_indices().find(|i| graph[*i] == "end").unwrap();
 
-    let paths = all_paths(&graph, start, end, &mut vec![start], exclude);
+    let paths = all_paths(&graph, start, end, &[start], exclude);
 
     paths.len()
 }
@@ -96,22 +115,22 @@ pub fn all_paths(
     graph: &Graph<&str, i32, Undirected>,
     start: NodeIndex,
     end: NodeIndex,
-    path: &mut Vec<NodeIndex>,
-    exclude: fn(graph: &Graph<&str, i32, Undirected>, path: &Vec<NodeIndex>, n: NodeIndex) -> bool,
+    path: &[NodeIndex],
+    exclude: Exclude,
 ) -> Vec<Vec<NodeIndex>> {
     let mut paths = vec![];
     for n in graph.neighbors(start) {
         if n == end {
-            let mut path = path.clone();
+            let mut path = path.to_vec();
             path.push(n);
             paths.push(path);
         } else if path.len() < 50 {
-            if exclude(&graph, &path, n) {
+            if exclude(graph, path, n) {
                 continue;
             }
-            let mut path = path.clone();
+            let mut path = path.to_vec();
             path.push(n);
-            paths.append(&mut all_paths(graph, n, end, &mut path, exclude));
+            paths.append(&mut all_paths(graph, n, end, &path, exclude));
         }
     }
     paths
@@ -123,14 +142,14 @@ mod tests {
 
     #[test]
     fn challenge1() {
-        let read = input::read_file(12, 0);
+        let read = input::read_file(&input::Key::example(12));
         let unique_paths = count_unique_paths(read, lowercase_only_once);
         assert_eq!(unique_paths, 19);
     }
 
     #[test]
     fn challenge2() {
-        let read = input::read_file(12, 0);
+        let read = input::read_file(&input::Key::example(12));
         let unique_paths = count_unique_paths(read, lowercase_only_one_twice);
         assert_eq!(unique_paths, 103);
     }
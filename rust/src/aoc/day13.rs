@@ -1,14 +1,13 @@
 use crate::aoc::input;
+use crate::aoc::schema::{Schema, Section, Token};
 
-use std::collections::BTreeMap;
-
-pub fn main(day: usize) {
-    println!("...1: {}", challenge1(day));
-    println!("...2: {}", challenge2(day));
+pub fn main(key: &input::Key) {
+    println!("...1: {}", challenge1(key));
+    println!("...2: {}", challenge2(key));
 }
 
-pub fn challenge1(day: usize) -> String {
-    let read = input::read_file(day, 1);
+pub fn challenge1(key: &input::Key) -> String {
+    let read = input::read_file(key);
     let (dots, cmds) = split(read);
     let mut grid = grid(dots);
     fold(&mut grid, cmds[0]);
@@ -19,8 +18,8 @@ pub fn challenge1(day: usize) -> String {
     )
 }
 
-pub fn challenge2(day: usize) -> String {
-    let read = input::read_file(day, 1);
+pub fn challenge2(key: &input::Key) -> String {
+    let read = input::read_file(key);
     let (dots, cmds) = split(read);
     let mut grid = grid(dots);
     for c in cmds {
@@ -29,14 +28,42 @@ pub fn challenge2(day: usize) -> String {
     format!("Code after folding:\n{}", format_grid(&grid))
 }
 
-pub fn split(read: String) -> (Vec<(usize, usize)>, Vec<(isize, isize)>) {
+/// Largest coordinate a dot or fold may have. The paper is a dense grid of
+/// every point up to the furthest dot, so this keeps it to a few megabytes.
+pub const MAX_COORDINATE: i64 = 4_095;
+
+pub fn schema() -> Schema {
+    Schema::sections(vec![
+        Section::new(
+            "dots",
+            vec![
+                Token::Number(0, MAX_COORDINATE),
+                Token::Literal(","),
+                Token::Number(0, MAX_COORDINATE),
+            ],
+        ),
+        Section::new(
+            "folds",
+            vec![
+                Token::Literal("fold along "),
+                Token::OneOf(&["x", "y"]),
+                Token::Literal("="),
+                Token::Number(1, MAX_COORDINATE),
+            ],
+        ),
+    ])
+}
+
+pub type Dots = Vec<(usize, usize)>;
+pub type Folds = Vec<(isize, isize)>;
+
+pub fn split(read: String) -> (Dots, Folds) {
     let lines = read.lines();
     let fold = lines.fold(String::new(), |a, l| a + l + "\n");
     let sep = fold.split("\n\n").collect::<Vec<&str>>();
 
     let dots = sep
-        .iter()
-        .nth(0)
+        .first()
         .unwrap()
         .lines()
         .map(|l| {
@@ -46,8 +73,7 @@ pub fn split(read: String) -> (Vec<(usize, usize)>, Vec<(isize, isize)>) {
         .collect();
 
     let cmds = sep
-        .iter()
-        .nth(1)
+        .get(1)
         .unwrap()
         .lines()
         .map(|l| {
@@ -73,7 +99,7 @@ pub fn grid(dots: Vec<(usize, usize)>) -> Vec<Vec<bool>> {
     grid
 }
 
-pub fn format_grid(grid: &Vec<Vec<bool>>) -> String {
+pub fn format_grid(grid: &[Vec<bool>]) -> String {
     grid.iter()
         .map(|r| {
             r.iter()
@@ -108,8 +134,8 @@ pub fn fold(grid: &mut Vec<Vec<bool>>, fold: (isize, isize)) {
     }
 }
 
-pub fn count_dots(grid: &Vec<Vec<bool>>) -> usize {
-    grid.into_iter().flatten().filter(|c| **c).count()
+pub fn count_dots(grid: &[Vec<bool>]) -> usize {
+    grid.iter().flatten().filter(|c| **c).count()
 }
 
 #[cfg(test)]
@@ -118,7 +144,7 @@ mod tests {
 
     #[test]
     fn challenge1() {
-        let read = input::read_file(13, 0);
+        let read = input::read_file(&input::Key::example(13));
         let (dots, cmds) = split(read);
         let mut grid = grid(dots);
         fold(&mut grid, cmds[0]);
@@ -127,12 +153,21 @@ mod tests {
 
     #[test]
     fn challenge2() {
-        let read = input::read_file(13, 0);
+        let read = input::read_file(&input::Key::example(13));
+        let (dots, cmds) = split(read);
+        let mut grid = grid(dots);
+        for c in cmds {
+            fold(&mut grid, c);
+        }
+        assert_eq!(
+            format_grid(&grid),
+            "#####\n#   #\n#   #\n#   #\n#####\n     \n     "
+        );
     }
 
     #[test]
     fn split_works() {
-        let read = input::read_file(13, 0);
+        let read = input::read_file(&input::Key::example(13));
         let (dots, cmds) = split(read);
         assert_eq!(
             dots,
@@ -162,7 +197,7 @@ mod tests {
 
     #[test]
     fn grid_works() {
-        let read = input::read_file(13, 0);
+        let read = input::read_file(&input::Key::example(13));
         let (dots, _) = split(read);
         let grid = grid(dots);
         let should = vec![
@@ -217,7 +252,7 @@ mod tests {
 
     #[test]
     fn fold_works() {
-        let read = input::read_file(13, 0);
+        let read = input::read_file(&input::Key::example(13));
         let (dots, cmds) = split(read);
         let mut grid = grid(dots);
         for c in cmds {
@@ -236,4 +271,15 @@ mod tests {
         ];
         assert_eq!(grid, should);
     }
+
+    #[test]
+    fn schema_bounds_coordinates() {
+        assert_eq!(schema().validate("6,10\n0,14\n\nfold along y=7\n"), vec![]);
+        assert_eq!(
+            schema()
+                .validate("6,10\n0,99999999999\n\nfold along y=7\n")
+                .len(),
+            1
+        );
+    }
 }
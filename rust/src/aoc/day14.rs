@@ -0,0 +1,199 @@
+use crate::aoc::input;
+use crate::aoc::schema::{Schema, Section, Token};
+
+use std::collections::HashMap;
+
+pub fn main(key: &input::Key) {
+    println!("...1: {}", challenge1(key));
+    println!("...2: {}", challenge2(key));
+}
+
+pub fn challenge1(key: &input::Key) -> String {
+    let (template, rules) = read_polymer(&input::read_file(key));
+    let counts = element_counts(&template, &rules, 10, Model::Pairs);
+    format!(
+        "Most minus least common element after {} steps: {}",
+        10,
+        spread(&counts)
+    )
+}
+
+pub fn challenge2(key: &input::Key) -> String {
+    let (template, rules) = read_polymer(&input::read_file(key));
+    let counts = element_counts(&template, &rules, 40, Model::Pairs);
+    format!(
+        "Most minus least common element after {} steps: {}",
+        40,
+        spread(&counts)
+    )
+}
+
+pub fn schema() -> Schema {
+    const ELEMENTS: &str = "ABCDEFGHIJKLMNOPQRSTUVWXYZ";
+    Schema::sections(vec![
+        Section::new("template", vec![Token::Chars(ELEMENTS)]).lines(1, 1),
+        Section::new(
+            "rules",
+            vec![
+                Token::Char(ELEMENTS),
+                Token::Char(ELEMENTS),
+                Token::Literal(" -> "),
+                Token::Char(ELEMENTS),
+            ],
+        ),
+    ])
+}
+
+pub type Rules = HashMap<(char, char), char>;
+
+pub fn read_polymer(read: &str) -> (String, Rules) {
+    let mut lines = read.lines();
+    let template = lines.next().unwrap().to_string();
+    let rules = lines
+        .filter(|l| !l.is_empty())
+        .map(|l| {
+            let s = l.split(" -> ").collect::<Vec<_>>();
+            let pair = s[0].chars().collect::<Vec<_>>();
+            ((pair[0], pair[1]), s[1].chars().next().unwrap())
+        })
+        .collect();
+    (template, rules)
+}
+
+/// How to run the insertion steps: `Naive` builds the polymer string and is
+/// only usable for a few steps, `Pairs` tracks pair frequencies instead.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Model {
+    Naive,
+    Pairs,
+}
+
+pub fn element_counts(
+    template: &str,
+    rules: &Rules,
+    steps: usize,
+    model: Model,
+) -> HashMap<char, usize> {
+    match model {
+        Model::Naive => count_elements(&expand(template, rules, steps)),
+        Model::Pairs => count_pairs(template, rules, steps),
+    }
+}
+
+pub fn expand(template: &str, rules: &Rules, steps: usize) -> String {
+    let mut polymer = template.to_string();
+    for _ in 0..steps {
+        let chars = polymer.chars().collect::<Vec<_>>();
+        let mut next = String::with_capacity(chars.len() * 2);
+        for w in chars.windows(2) {
+            next.push(w[0]);
+            if let Some(insert) = rules.get(&(w[0], w[1])) {
+                next.push(*insert);
+            }
+        }
+        if let Some(last) = chars.last() {
+            next.push(*last);
+        }
+        polymer = next;
+    }
+    polymer
+}
+
+pub fn count_elements(polymer: &str) -> HashMap<char, usize> {
+    let mut counts = HashMap::new();
+    for c in polymer.chars() {
+        *counts.entry(c).or_insert(0) += 1;
+    }
+    counts
+}
+
+pub fn count_pairs(template: &str, rules: &Rules, steps: usize) -> HashMap<char, usize> {
+    let chars = template.chars().collect::<Vec<_>>();
+    let mut pairs = HashMap::<(char, char), usize>::new();
+    for w in chars.windows(2) {
+        *pairs.entry((w[0], w[1])).or_insert(0) += 1;
+    }
+    for _ in 0..steps {
+        let mut new_pairs = HashMap::<(char, char), usize>::new();
+        for (&(a, b), count) in pairs.iter() {
+            match rules.get(&(a, b)) {
+                Some(&c) => {
+                    *new_pairs.entry((a, c)).or_insert(0) += count;
+                    *new_pairs.entry((c, b)).or_insert(0) += count;
+                }
+                None => *new_pairs.entry((a, b)).or_insert(0) += count,
+            }
+        }
+        pairs = new_pairs;
+    }
+    // Every element starts exactly one pair, except the last one which
+    // never changes.
+    let mut counts = HashMap::new();
+    for ((a, _), count) in pairs {
+        *counts.entry(a).or_insert(0) += count;
+    }
+    if let Some(last) = chars.last() {
+        *counts.entry(*last).or_insert(0) += 1;
+    }
+    counts
+}
+
+pub fn spread(counts: &HashMap<char, usize>) -> usize {
+    let max = counts.values().max().unwrap_or(&0);
+    let min = counts.values().min().unwrap_or(&0);
+    max - min
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn challenge1() {
+        let (template, rules) = read_polymer(&input::read_file(&input::Key::example(14)));
+        let counts = element_counts(&template, &rules, 10, Model::Pairs);
+        assert_eq!(counts[&'B'], 1749);
+        assert_eq!(counts[&'C'], 298);
+        assert_eq!(counts[&'H'], 161);
+        assert_eq!(counts[&'N'], 865);
+        assert_eq!(spread(&counts), 1588);
+    }
+
+    #[test]
+    fn challenge2() {
+        let (template, rules) = read_polymer(&input::read_file(&input::Key::example(14)));
+        let counts = element_counts(&template, &rules, 40, Model::Pairs);
+        assert_eq!(counts[&'B'], 2192039569602);
+        assert_eq!(counts[&'H'], 3849876073);
+        assert_eq!(spread(&counts), 2188189693529);
+    }
+
+    #[test]
+    fn expand_works() {
+        let (template, rules) = read_polymer(&input::read_file(&input::Key::example(14)));
+        assert_eq!(expand(&template, &rules, 1), "NCNBCHB");
+        assert_eq!(expand(&template, &rules, 2), "NBCCNBBBCBHCB");
+        assert_eq!(expand(&template, &rules, 3), "NBBBCNCCNBBNBNBBCHBHHBCHB");
+        assert_eq!(expand(&template, &rules, 5).len(), 97);
+    }
+
+    #[test]
+    fn models_agree() {
+        let (template, rules) = read_polymer(&input::read_file(&input::Key::example(14)));
+        for steps in 0..=10 {
+            assert_eq!(
+                element_counts(&template, &rules, steps, Model::Naive),
+                element_counts(&template, &rules, steps, Model::Pairs),
+                "steps: {}",
+                steps
+            );
+        }
+    }
+
+    #[test]
+    fn schema_needs_pairs() {
+        assert_eq!(schema().validate("NNCB\n\nCH -> B\n"), vec![]);
+        assert_eq!(schema().validate("NNCB\n\nC -> B\n").len(), 1);
+        assert_eq!(schema().validate("NNCB\n\nCH -> BC\n").len(), 1);
+    }
+}
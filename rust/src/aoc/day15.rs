@@ -0,0 +1,172 @@
+use crate::aoc::input;
+use crate::aoc::schema::Schema;
+
+use petgraph::algo::astar;
+use petgraph::prelude::*;
+
+pub fn main(key: &input::Key) {
+    println!("...1: {}", challenge1(key));
+    println!("...2: {}", challenge2(key));
+}
+
+pub fn challenge1(key: &input::Key) -> String {
+    let grid = read_risks(&input::read_file(key));
+    let (risk, _) = lowest_risk(&grid, Search::AStar).unwrap();
+    format!("Lowest total risk of any path: {}", risk)
+}
+
+pub fn challenge2(key: &input::Key) -> String {
+    let grid = tile(&read_risks(&input::read_file(key)), 5);
+    let (risk, _) = lowest_risk(&grid, Search::AStar).unwrap();
+    format!("Lowest total risk on the full map: {}", risk)
+}
+
+pub fn schema() -> Schema {
+    Schema::grid("123456789")
+}
+
+pub fn read_risks(read: &str) -> Vec<Vec<usize>> {
+    read.lines()
+        .map(|l| {
+            l.chars()
+                .map(|c| c.to_digit(10).unwrap() as usize)
+                .collect::<Vec<_>>()
+        })
+        .collect::<Vec<_>>()
+}
+
+/// Repeats the grid `times` in both directions, adding the tile distance to
+/// each risk and wrapping values above 9 back to 1.
+pub fn tile(grid: &[Vec<usize>], times: usize) -> Vec<Vec<usize>> {
+    let rows = grid.len();
+    let cols = grid[0].len();
+    (0..rows * times)
+        .map(|r| {
+            (0..cols * times)
+                .map(|c| (grid[r % rows][c % cols] + r / rows + c / cols - 1) % 9 + 1)
+                .collect()
+        })
+        .collect()
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Search {
+    Dijkstra,
+    /// A* with the Manhattan distance to the goal, which never overestimates
+    /// since every step costs at least 1.
+    AStar,
+}
+
+/// Lowest total risk from the top left to the bottom right corner and the
+/// path taken as `(row, col)` positions, the start included.
+pub fn lowest_risk(grid: &[Vec<usize>], search: Search) -> Option<(usize, Vec<(usize, usize)>)> {
+    let rows = grid.len();
+    let cols = grid.first()?.len();
+    let mut graph = DiGraph::<(usize, usize), usize>::with_capacity(rows * cols, rows * cols * 4);
+    for r in 0..rows {
+        for c in 0..cols {
+            graph.add_node((r, c));
+        }
+    }
+    let index = |r: usize, c: usize| NodeIndex::new(r * cols + c);
+    for r in 0..rows {
+        for c in 0..cols {
+            if r + 1 < rows {
+                graph.add_edge(index(r, c), index(r + 1, c), grid[r + 1][c]);
+                graph.add_edge(index(r + 1, c), index(r, c), grid[r][c]);
+            }
+            if c + 1 < cols {
+                graph.add_edge(index(r, c), index(r, c + 1), grid[r][c + 1]);
+                graph.add_edge(index(r, c + 1), index(r, c), grid[r][c]);
+            }
+        }
+    }
+
+    let goal = index(rows - 1, cols - 1);
+    let (risk, path) = astar(
+        &graph,
+        index(0, 0),
+        |n| n == goal,
+        |e| *e.weight(),
+        |n| match search {
+            Search::Dijkstra => 0,
+            Search::AStar => {
+                let (r, c) = graph[n];
+                (rows - 1 - r) + (cols - 1 - c)
+            }
+        },
+    )?;
+    Some((risk, path.iter().map(|n| graph[*n]).collect()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn challenge1() {
+        let grid = read_risks(&input::read_file(&input::Key::example(15)));
+        let (risk, path) = lowest_risk(&grid, Search::AStar).unwrap();
+        assert_eq!(risk, 40);
+        assert_eq!(path.first(), Some(&(0, 0)));
+        assert_eq!(path.last(), Some(&(9, 9)));
+        assert_eq!(
+            path.iter().skip(1).map(|&(r, c)| grid[r][c]).sum::<usize>(),
+            40
+        );
+    }
+
+    #[test]
+    fn challenge2() {
+        let grid = tile(&read_risks(&input::read_file(&input::Key::example(15))), 5);
+        let (risk, _) = lowest_risk(&grid, Search::AStar).unwrap();
+        assert_eq!(risk, 315);
+    }
+
+    #[test]
+    fn searches_agree() {
+        let grid = tile(&read_risks(&input::read_file(&input::Key::example(15))), 2);
+        let (dijkstra, _) = lowest_risk(&grid, Search::Dijkstra).unwrap();
+        let (astar, _) = lowest_risk(&grid, Search::AStar).unwrap();
+        assert_eq!(dijkstra, astar);
+    }
+
+    #[test]
+    fn tile_works() {
+        let grid = tile(&[vec![8]], 5);
+        assert_eq!(
+            grid,
+            vec![
+                vec![8, 9, 1, 2, 3],
+                vec![9, 1, 2, 3, 4],
+                vec![1, 2, 3, 4, 5],
+                vec![2, 3, 4, 5, 6],
+                vec![3, 4, 5, 6, 7],
+            ]
+        );
+        let grid = tile(&read_risks(&input::read_file(&input::Key::example(15))), 5);
+        assert_eq!(grid.len(), 50);
+        assert_eq!(grid[0][10..20], [2, 2, 7, 4, 8, 6, 2, 8, 5, 3]);
+        assert_eq!(grid[49][40..50], [1, 2, 9, 9, 8, 3, 3, 4, 7, 9]);
+    }
+
+    #[test]
+    fn lowest_risk_prefers_detours() {
+        let grid = vec![vec![1, 9, 1, 1, 1], vec![1, 1, 1, 9, 1]];
+        let (risk, path) = lowest_risk(&grid, Search::Dijkstra).unwrap();
+        assert_eq!(risk, 7);
+        assert_eq!(
+            path,
+            vec![
+                (0, 0),
+                (1, 0),
+                (1, 1),
+                (1, 2),
+                (0, 2),
+                (0, 3),
+                (0, 4),
+                (1, 4)
+            ]
+        );
+    }
+}
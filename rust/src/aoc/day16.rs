@@ -0,0 +1,498 @@
+use crate::aoc::input;
+use crate::aoc::schema::{Schema, Section, Token};
+
+use std::fmt;
+
+pub fn main(key: &input::Key) {
+    println!("...1: {}", challenge1(key));
+    println!("...2: {}", challenge2(key));
+}
+
+pub fn challenge1(key: &input::Key) -> String {
+    match decode(input::read_file(key).trim_end()) {
+        Ok(packet) => format!("Sum of all version numbers: {}", version_sum(&packet)),
+        Err(err) => format!("Malformed transmission: {}", err),
+    }
+}
+
+pub fn challenge2(key: &input::Key) -> String {
+    match decode(input::read_file(key).trim_end()).map(|p| p.evaluate()) {
+        Ok(Some(value)) => format!("Value of the outermost packet: {}", value),
+        Ok(None) => "Value of the outermost packet overflows".to_string(),
+        Err(err) => format!("Malformed transmission: {}", err),
+    }
+}
+
+/// The transmission written out as an expression, with its value.
+pub fn expression(key: &input::Key) -> String {
+    match decode(input::read_file(key).trim_end()) {
+        Ok(packet) => match packet.evaluate() {
+            Some(value) => format!("{} = {}", packet, value),
+            None => format!("{} overflows", packet),
+        },
+        Err(err) => format!("Malformed transmission: {}", err),
+    }
+}
+
+pub fn schema() -> Schema {
+    Schema::sections(vec![Section::new(
+        "transmission",
+        vec![Token::Chars("0123456789ABCDEF")],
+    )
+    .lines(1, 1)])
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DecodeError {
+    InvalidHex {
+        pos: usize,
+        found: char,
+    },
+    Truncated {
+        bit: usize,
+        needed: usize,
+    },
+    LiteralOverflow {
+        bit: usize,
+    },
+    LengthMismatch {
+        bit: usize,
+        expected: usize,
+        found: usize,
+    },
+    Operands {
+        bit: usize,
+        op: Op,
+        found: usize,
+    },
+    TrailingData {
+        bit: usize,
+    },
+}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            DecodeError::InvalidHex { pos, found } => {
+                write!(f, "invalid hex digit {:?} at position {}", found, pos)
+            }
+            DecodeError::Truncated { bit, needed } => {
+                write!(f, "truncated at bit {}, {} more bits needed", bit, needed)
+            }
+            DecodeError::LiteralOverflow { bit } => {
+                write!(f, "literal at bit {} does not fit into 64 bits", bit)
+            }
+            DecodeError::LengthMismatch {
+                bit,
+                expected,
+                found,
+            } => write!(
+                f,
+                "sub-packets at bit {} span {} bits instead of {}",
+                bit, found, expected
+            ),
+            DecodeError::Operands { bit, op, found } => write!(
+                f,
+                "{} at bit {} cannot take {} operands",
+                op.name(),
+                bit,
+                found
+            ),
+            DecodeError::TrailingData { bit } => {
+                write!(
+                    f,
+                    "unexpected data after the outermost packet at bit {}",
+                    bit
+                )
+            }
+        }
+    }
+}
+
+/// A hex transmission unpacked into single bits, read front to back.
+pub struct Bits {
+    bits: Vec<bool>,
+    pos: usize,
+}
+
+impl Bits {
+    pub fn from_hex(hex: &str) -> Result<Bits, DecodeError> {
+        let mut bits = Vec::with_capacity(hex.len() * 4);
+        for (pos, c) in hex.chars().enumerate() {
+            let nibble = c
+                .to_digit(16)
+                .ok_or(DecodeError::InvalidHex { pos, found: c })?;
+            for shift in (0..4).rev() {
+                bits.push(nibble >> shift & 1 == 1);
+            }
+        }
+        Ok(Bits { bits, pos: 0 })
+    }
+    pub fn pos(&self) -> usize {
+        self.pos
+    }
+    pub fn read(&mut self, n: usize) -> Result<u64, DecodeError> {
+        if self.pos + n > self.bits.len() {
+            return Err(DecodeError::Truncated {
+                bit: self.pos,
+                needed: self.pos + n - self.bits.len(),
+            });
+        }
+        let value = self.bits[self.pos..self.pos + n]
+            .iter()
+            .fold(0, |v, b| v << 1 | *b as u64);
+        self.pos += n;
+        Ok(value)
+    }
+    fn rest_is_padding(&self) -> bool {
+        self.bits[self.pos..].iter().all(|b| !b)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Op {
+    Sum,
+    Product,
+    Minimum,
+    Maximum,
+    GreaterThan,
+    LessThan,
+    EqualTo,
+}
+
+impl Op {
+    fn from_type(type_id: u64) -> Option<Op> {
+        match type_id {
+            0 => Some(Op::Sum),
+            1 => Some(Op::Product),
+            2 => Some(Op::Minimum),
+            3 => Some(Op::Maximum),
+            5 => Some(Op::GreaterThan),
+            6 => Some(Op::LessThan),
+            7 => Some(Op::EqualTo),
+            _ => None,
+        }
+    }
+    pub fn name(&self) -> &'static str {
+        match self {
+            Op::Sum => "sum",
+            Op::Product => "product",
+            Op::Minimum => "min",
+            Op::Maximum => "max",
+            Op::GreaterThan => ">",
+            Op::LessThan => "<",
+            Op::EqualTo => "==",
+        }
+    }
+    fn is_comparison(&self) -> bool {
+        matches!(self, Op::GreaterThan | Op::LessThan | Op::EqualTo)
+    }
+}
+
+/// How an operator packet announced its sub-packets.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Length {
+    Bits(usize),
+    Packets(usize),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Packet {
+    Literal {
+        version: u8,
+        value: u64,
+    },
+    Operator {
+        version: u8,
+        op: Op,
+        length: Length,
+        packets: Vec<Packet>,
+    },
+}
+
+impl Packet {
+    pub fn version(&self) -> u8 {
+        match self {
+            Packet::Literal { version, .. } => *version,
+            Packet::Operator { version, .. } => *version,
+        }
+    }
+    /// Calls `f` for this packet and all nested packets, depth first.
+    pub fn visit(&self, f: &mut impl FnMut(&Packet)) {
+        f(self);
+        if let Packet::Operator { packets, .. } = self {
+            for p in packets {
+                p.visit(f);
+            }
+        }
+    }
+    /// The value of the expression, or `None` if a sum or product does not
+    /// fit in a `u64`.
+    pub fn evaluate(&self) -> Option<u64> {
+        match self {
+            Packet::Literal { value, .. } => Some(*value),
+            Packet::Operator { op, packets, .. } => {
+                let values = packets
+                    .iter()
+                    .map(|p| p.evaluate())
+                    .collect::<Option<Vec<_>>>()?;
+                let mut values = values.into_iter();
+                match op {
+                    Op::Sum => values.try_fold(0u64, |a, v| a.checked_add(v)),
+                    Op::Product => values.try_fold(1u64, |a, v| a.checked_mul(v)),
+                    Op::Minimum => values.min(),
+                    Op::Maximum => values.max(),
+                    Op::GreaterThan => Some((values.next() > values.next()) as u64),
+                    Op::LessThan => Some((values.next() < values.next()) as u64),
+                    Op::EqualTo => Some((values.next() == values.next()) as u64),
+                }
+            }
+        }
+    }
+}
+
+impl fmt::Display for Packet {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Packet::Literal { value, .. } => write!(f, "{}", value),
+            Packet::Operator { op, packets, .. } if op.is_comparison() => {
+                write!(f, "({} {} {})", packets[0], op.name(), packets[1])
+            }
+            Packet::Operator { op, packets, .. } => {
+                write!(f, "{}(", op.name())?;
+                for (i, p) in packets.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{}", p)?;
+                }
+                write!(f, ")")
+            }
+        }
+    }
+}
+
+pub fn decode(hex: &str) -> Result<Packet, DecodeError> {
+    let mut bits = Bits::from_hex(hex)?;
+    let packet = read_packet(&mut bits)?;
+    if !bits.rest_is_padding() {
+        return Err(DecodeError::TrailingData { bit: bits.pos() });
+    }
+    Ok(packet)
+}
+
+pub fn read_packet(bits: &mut Bits) -> Result<Packet, DecodeError> {
+    let start = bits.pos();
+    let version = bits.read(3)? as u8;
+    let type_id = bits.read(3)?;
+    let op = match Op::from_type(type_id) {
+        Some(op) => op,
+        None => return read_literal(bits, version),
+    };
+    let (length, packets) = match bits.read(1)? {
+        0 => {
+            let len = bits.read(15)? as usize;
+            let begin = bits.pos();
+            let mut packets = vec![];
+            while bits.pos() < begin + len {
+                packets.push(read_packet(bits)?);
+            }
+            if bits.pos() != begin + len {
+                return Err(DecodeError::LengthMismatch {
+                    bit: begin,
+                    expected: len,
+                    found: bits.pos() - begin,
+                });
+            }
+            (Length::Bits(len), packets)
+        }
+        _ => {
+            let count = bits.read(11)? as usize;
+            let packets = (0..count)
+                .map(|_| read_packet(bits))
+                .collect::<Result<Vec<_>, _>>()?;
+            (Length::Packets(count), packets)
+        }
+    };
+    let operands_ok = match op.is_comparison() {
+        true => packets.len() == 2,
+        false => !packets.is_empty(),
+    };
+    if !operands_ok {
+        return Err(DecodeError::Operands {
+            bit: start,
+            op,
+            found: packets.len(),
+        });
+    }
+    Ok(Packet::Operator {
+        version,
+        op,
+        length,
+        packets,
+    })
+}
+
+fn read_literal(bits: &mut Bits, version: u8) -> Result<Packet, DecodeError> {
+    let start = bits.pos();
+    let mut value: u64 = 0;
+    loop {
+        let group = bits.read(5)?;
+        if value >> 60 != 0 {
+            return Err(DecodeError::LiteralOverflow { bit: start });
+        }
+        value = value << 4 | (group & 0xf);
+        if group & 0x10 == 0 {
+            break;
+        }
+    }
+    Ok(Packet::Literal { version, value })
+}
+
+pub fn version_sum(packet: &Packet) -> usize {
+    let mut sum = 0;
+    packet.visit(&mut |p| sum += p.version() as usize);
+    sum
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn challenge1() {
+        let sums = vec![
+            ("8A004A801A8002F478", 16),
+            ("620080001611562C8802118E34", 12),
+            ("A0016C880162017C3686B18A3D4780", 31),
+        ];
+        for (hex, sum) in sums {
+            assert_eq!(version_sum(&decode(hex).unwrap()), sum, "{}", hex);
+        }
+    }
+
+    #[test]
+    fn challenge2() {
+        let values = vec![
+            ("C200B40A82", 3),
+            ("04005AC33890", 54),
+            ("880086C3E88112", 7),
+            ("CE00C43D881120", 9),
+            ("D8005AC2A8F0", 1),
+            ("F600BC2D8F", 0),
+            ("9C005AC2F8F0", 0),
+            ("9C0141080250320F1802104A08", 1),
+        ];
+        for (hex, value) in values {
+            assert_eq!(decode(hex).unwrap().evaluate(), Some(value), "{}", hex);
+        }
+    }
+
+    #[test]
+    fn evaluate_overflow() {
+        let big = |value| Packet::Literal { version: 0, value };
+        let operator = |op, packets| Packet::Operator {
+            version: 0,
+            op,
+            length: Length::Packets(2),
+            packets,
+        };
+        let sum = operator(Op::Sum, vec![big(u64::MAX), big(1)]);
+        assert_eq!(sum.evaluate(), None);
+        let product = operator(Op::Product, vec![big(1 << 32), big(1 << 32)]);
+        assert_eq!(product.evaluate(), None);
+        assert_eq!(
+            operator(Op::LessThan, vec![product, big(1)]).evaluate(),
+            None
+        );
+        let fits = operator(Op::Product, vec![big(1 << 31), big(1 << 32)]);
+        assert_eq!(fits.evaluate(), Some(1 << 63));
+    }
+
+    #[test]
+    fn expression_shows_the_tree() {
+        let key = input::Key::new(input::YEAR, 16, "expression");
+        input::provide_inline(&key, "9C0141080250320F1802104A08\n");
+        assert_eq!(super::challenge2(&key), "Value of the outermost packet: 1");
+        assert_eq!(expression(&key), "(sum(1, 3) == product(2, 2)) = 1");
+    }
+
+    #[test]
+    fn decode_literal() {
+        assert_eq!(
+            decode("D2FE28").unwrap(),
+            Packet::Literal {
+                version: 6,
+                value: 2021
+            }
+        );
+    }
+
+    #[test]
+    fn decode_operators() {
+        assert_eq!(
+            decode("38006F45291200").unwrap(),
+            Packet::Operator {
+                version: 1,
+                op: Op::LessThan,
+                length: Length::Bits(27),
+                packets: vec![
+                    Packet::Literal {
+                        version: 6,
+                        value: 10
+                    },
+                    Packet::Literal {
+                        version: 2,
+                        value: 20
+                    },
+                ],
+            }
+        );
+        let packet = decode("EE00D40C823060").unwrap();
+        assert_eq!(packet.version(), 7);
+        assert_eq!(packet.to_string(), "max(1, 2, 3)");
+    }
+
+    #[test]
+    fn pretty_print() {
+        assert_eq!(decode("C200B40A82").unwrap().to_string(), "sum(1, 2)");
+        assert_eq!(decode("F600BC2D8F").unwrap().to_string(), "(5 > 15)");
+        assert_eq!(
+            decode("9C0141080250320F1802104A08").unwrap().to_string(),
+            "(sum(1, 3) == product(2, 2))"
+        );
+    }
+
+    #[test]
+    fn decode_errors() {
+        assert_eq!(
+            decode("D2FG28").err(),
+            Some(DecodeError::InvalidHex { pos: 3, found: 'G' })
+        );
+        assert_eq!(
+            decode("D2FE").err(),
+            Some(DecodeError::Truncated { bit: 16, needed: 5 })
+        );
+        assert_eq!(
+            decode("38006F452912").err(),
+            Some(DecodeError::Truncated { bit: 44, needed: 1 })
+        );
+        assert_eq!(
+            decode("D2FE28FF").err(),
+            Some(DecodeError::TrailingData { bit: 21 })
+        );
+        assert_eq!(
+            decode("D3FFFFFFFFFFFFFFFFFFFC0").err(),
+            Some(DecodeError::LiteralOverflow { bit: 6 })
+        );
+        // `<` with a single literal operand.
+        assert_eq!(
+            decode("1A004428").err(),
+            Some(DecodeError::Operands {
+                bit: 0,
+                op: Op::LessThan,
+                found: 1
+            })
+        );
+    }
+}
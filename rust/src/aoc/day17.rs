@@ -0,0 +1,316 @@
+use crate::aoc::input;
+use crate::aoc::schema::{Schema, Token, Violation};
+
+use recap::Recap;
+use serde::Deserialize;
+
+pub fn main(key: &input::Key) {
+    println!("...1: {}", challenge1(key));
+    println!("...2: {}", challenge2(key));
+}
+
+pub fn challenge1(key: &input::Key) -> String {
+    let target = match read_target(&input::read_file(key)) {
+        Ok(target) => target,
+        Err(err) => return format!("Invalid target area: {}", err),
+    };
+    match target.max_apex() {
+        Some(apex) => format!("Highest y position reached: {}", apex),
+        None => "No initial velocity hits the target".to_string(),
+    }
+}
+
+pub fn challenge2(key: &input::Key) -> String {
+    let target = match read_target(&input::read_file(key)) {
+        Ok(target) => target,
+        Err(err) => return format!("Invalid target area: {}", err),
+    };
+    format!(
+        "Distinct initial velocities hitting the target: {}",
+        target.velocities().len()
+    )
+}
+
+/// Coordinates are capped so that the velocity search, which is quadratic in
+/// the distance to the target, stays fast.
+pub fn schema() -> Schema {
+    let coordinate = || Token::Number(-LIMIT, LIMIT);
+    Schema::lines(vec![
+        Token::Literal("target area: x="),
+        coordinate(),
+        Token::Literal(".."),
+        coordinate(),
+        Token::Literal(", y="),
+        coordinate(),
+        Token::Literal(".."),
+        coordinate(),
+    ])
+    .check(range_order)
+}
+
+const LIMIT: i64 = 1000;
+
+fn range_order(read: &str) -> Vec<Violation> {
+    let mut violations = vec![];
+    for (i, line) in read.lines().enumerate() {
+        for (pos, _) in line.match_indices('=') {
+            let range = line[pos + 1..].split(',').next().unwrap();
+            let bounds = range
+                .split_once("..")
+                .map(|(lo, hi)| (lo.parse::<i64>(), hi.parse::<i64>()));
+            if let Some((Ok(lo), Ok(hi))) = bounds {
+                if lo > hi {
+                    let message = format!("range {} runs backwards", range);
+                    violations.push(Violation::new(i + 1, pos + 2, &message));
+                }
+            }
+        }
+    }
+    violations
+}
+
+pub fn read_target(read: &str) -> Result<Target, String> {
+    let line = read.trim_end();
+    let target: Target = line
+        .parse()
+        .map_err(|_| format!("expected \"target area: x=A..B, y=C..D\", found {:?}", line))?;
+    let ranges = [("x", target.x1, target.x2), ("y", target.y1, target.y2)];
+    for (axis, lo, hi) in ranges {
+        if lo > hi {
+            return Err(format!("{} range {}..{} runs backwards", axis, lo, hi));
+        }
+        if lo < -LIMIT || hi > LIMIT {
+            return Err(format!("{} range {}..{} exceeds ±{}", axis, lo, hi, LIMIT));
+        }
+    }
+    Ok(target)
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Recap)]
+#[recap(regex = r"target area: x=(?P<x1>-?\d+)\.\.(?P<x2>-?\d+), y=(?P<y1>-?\d+)\.\.(?P<y2>-?\d+)")]
+pub struct Target {
+    x1: i64,
+    x2: i64,
+    y1: i64,
+    y2: i64,
+}
+
+impl Target {
+    pub fn contains(&self, (x, y): (i64, i64)) -> bool {
+        x >= self.x1 && x <= self.x2 && y >= self.y1 && y <= self.y2
+    }
+
+    /// Positions after each step, starting at the origin, until the probe is
+    /// inside the target or can no longer reach it.
+    pub fn trajectory(&self, (vx, vy): (i64, i64)) -> Vec<(i64, i64)> {
+        let (mut x, mut y, mut vx, mut vy) = (0, 0, vx, vy);
+        let mut path = vec![(0, 0)];
+        while !self.contains((x, y)) && !self.missed((x, y), (vx, vy)) {
+            x += vx;
+            y += vy;
+            vx -= vx.signum();
+            vy -= 1;
+            path.push((x, y));
+        }
+        path
+    }
+
+    pub fn hits(&self, velocity: (i64, i64)) -> bool {
+        let path = self.trajectory(velocity);
+        self.contains(*path.last().unwrap())
+    }
+
+    fn missed(&self, (x, y): (i64, i64), (vx, vy): (i64, i64)) -> bool {
+        (vy < 0 && y < self.y1) || (vx >= 0 && x > self.x2) || (vx <= 0 && x < self.x1)
+    }
+
+    /// Highest point reachable while still hitting the target. For a target
+    /// below the launcher that any horizontal speed can stall in, the probe
+    /// passes y=0 again with speed `-vy - 1`, so the fastest shot drops from
+    /// 0 straight to the bottom row: `vy = -y1 - 1`.
+    pub fn max_apex(&self) -> Option<i64> {
+        if self.y2 < 0 && self.stalling_vx().is_some() {
+            let vy = -self.y1 - 1;
+            return Some(vy * (vy + 1) / 2);
+        }
+        self.velocities()
+            .iter()
+            .map(|&(_, vy)| match vy > 0 {
+                true => vy * (vy + 1) / 2,
+                false => 0,
+            })
+            .max()
+    }
+
+    /// A horizontal speed whose drag brings the probe to rest above the
+    /// target.
+    fn stalling_vx(&self) -> Option<i64> {
+        let sign = match self.x2 < 0 {
+            true => -1,
+            false => 1,
+        };
+        (0..=self.x1.abs().max(self.x2.abs()))
+            .find(|v| {
+                let rest = sign * v * (v + 1) / 2;
+                rest >= self.x1 && rest <= self.x2
+            })
+            .map(|v| sign * v)
+    }
+
+    /// All initial velocities that hit the target. Horizontal speeds beyond
+    /// the far edge overshoot on the first step; vertical speeds beyond the
+    /// largest distance either overshoot rising or skip the target falling.
+    pub fn velocities(&self) -> Vec<(i64, i64)> {
+        let vx_range = self.x1.min(0)..=self.x2.max(0);
+        let vy_limit = self.y1.abs().max(self.y2.abs());
+        let vy_range = self.y1.min(0)..=vy_limit;
+        let mut velocities = vec![];
+        for vx in vx_range {
+            for vy in vy_range.clone() {
+                if self.hits((vx, vy)) {
+                    velocities.push((vx, vy));
+                }
+            }
+        }
+        velocities
+    }
+}
+
+pub fn format_trajectory(target: &Target, path: &[(i64, i64)]) -> String {
+    let xs = path.iter().map(|p| p.0).chain([target.x1, target.x2]);
+    let ys = path.iter().map(|p| p.1).chain([target.y1, target.y2]);
+    let (min_x, max_x) = (xs.clone().min().unwrap(), xs.max().unwrap());
+    let (min_y, max_y) = (ys.clone().min().unwrap(), ys.max().unwrap());
+    (min_y..=max_y)
+        .rev()
+        .map(|y| {
+            (min_x..=max_x)
+                .map(|x| match (x, y) {
+                    (0, 0) => 'S',
+                    p if path.contains(&p) => '#',
+                    p if target.contains(p) => 'T',
+                    _ => '.',
+                })
+                .collect::<String>()
+        })
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn example() -> Target {
+        read_target(&input::read_file(&input::Key::example(17))).unwrap()
+    }
+
+    #[test]
+    fn challenge1() {
+        assert_eq!(example().max_apex(), Some(45));
+    }
+
+    #[test]
+    fn challenge2() {
+        let velocities = example().velocities();
+        assert_eq!(velocities.len(), 112);
+        assert!(velocities.contains(&(6, 9)));
+        assert!(velocities.contains(&(30, -10)));
+        assert!(!velocities.contains(&(17, -4)));
+    }
+
+    #[test]
+    fn parse_works() {
+        assert_eq!(
+            example(),
+            Target {
+                x1: 20,
+                x2: 30,
+                y1: -10,
+                y2: -5
+            }
+        );
+    }
+
+    #[test]
+    fn invalid_targets() {
+        assert_eq!(
+            read_target("target area: x=30..20, y=-10..-5\n"),
+            Err("x range 30..20 runs backwards".to_string())
+        );
+        assert!(read_target("target area: x=20..30, y=-10..-5000\n").is_err());
+        let violations = schema().validate("target area: x=30..20, y=-10..-5\n");
+        assert_eq!(violations.len(), 1);
+        assert_eq!(violations[0].column, 16);
+        assert_eq!(
+            schema()
+                .validate("target area: x=20..30, y=-2000..-5\n")
+                .len(),
+            1
+        );
+    }
+
+    #[test]
+    fn max_apex_matches_search() {
+        let targets = vec![
+            "target area: x=20..30, y=-10..-5",
+            "target area: x=-30..-20, y=-10..-5",
+            "target area: x=5..8, y=3..6",
+        ];
+        for t in targets {
+            let target: Target = t.parse().unwrap();
+            let searched = target
+                .velocities()
+                .iter()
+                .map(|&(vx, vy)| {
+                    target
+                        .trajectory((vx, vy))
+                        .iter()
+                        .map(|p| p.1)
+                        .max()
+                        .unwrap()
+                })
+                .max();
+            assert_eq!(target.max_apex(), searched, "{}", t);
+        }
+    }
+
+    #[test]
+    fn trajectory_works() {
+        let target = example();
+        assert_eq!(
+            target.trajectory((7, 2)),
+            vec![
+                (0, 0),
+                (7, 2),
+                (13, 3),
+                (18, 3),
+                (22, 2),
+                (25, 0),
+                (27, -3),
+                (28, -7)
+            ]
+        );
+        assert!(!target.hits((17, -4)));
+        assert_eq!(
+            format_trajectory(&target, &target.trajectory((7, 2))),
+            [
+                ".............#....#............",
+                ".......#..............#........",
+                "...............................",
+                "S........................#.....",
+                "...............................",
+                "...............................",
+                "...........................#...",
+                "...............................",
+                "....................TTTTTTTTTTT",
+                "....................TTTTTTTTTTT",
+                "....................TTTTTTTT#TT",
+                "....................TTTTTTTTTTT",
+                "....................TTTTTTTTTTT",
+                "....................TTTTTTTTTTT",
+            ]
+            .join("\n")
+        );
+    }
+}
@@ -0,0 +1,368 @@
+use crate::aoc::input;
+use crate::aoc::schema::{Schema, Token, Violation};
+
+extern crate pest;
+use pest::Parser;
+use std::fmt;
+use std::ops::Add;
+use std::str::FromStr;
+
+pub fn main(key: &input::Key) {
+    println!("...1: {}", challenge1(key));
+    println!("...2: {}", challenge2(key));
+}
+
+pub fn challenge1(key: &input::Key) -> String {
+    let numbers = match read_numbers(&input::read_file(key)) {
+        Ok(numbers) => numbers,
+        Err(err) => return format!("Invalid snailfish numbers: {}", err),
+    };
+    match numbers.into_iter().reduce(|a, b| a + b) {
+        Some(sum) => format!("Magnitude of the final sum: {}", sum.magnitude()),
+        None => "No snailfish numbers to add".to_string(),
+    }
+}
+
+pub fn challenge2(key: &input::Key) -> String {
+    let numbers = match read_numbers(&input::read_file(key)) {
+        Ok(numbers) => numbers,
+        Err(err) => return format!("Invalid snailfish numbers: {}", err),
+    };
+    match largest_pair_sum(&numbers) {
+        Some((magnitude, _)) => {
+            format!("Largest magnitude of any sum of two numbers: {}", magnitude)
+        }
+        None => "Fewer than two snailfish numbers to add".to_string(),
+    }
+}
+
+pub fn schema() -> Schema {
+    Schema::lines(vec![Token::Chars("[],0123456789")]).check(numbers)
+}
+
+/// The tokens only pin down the alphabet; brackets must also balance and
+/// every number must already be reduced.
+fn numbers(read: &str) -> Vec<Violation> {
+    read.lines()
+        .enumerate()
+        .filter_map(|(i, line)| {
+            read_number(line)
+                .err()
+                .map(|(column, message)| Violation::new(i + 1, column, &message))
+        })
+        .collect()
+}
+
+#[derive(pest_derive::Parser)]
+#[grammar = "aoc/day18.pest"]
+pub struct SnailfishParser;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Snailfish {
+    Regular(u32),
+    Pair(Box<Snailfish>, Box<Snailfish>),
+}
+
+use Snailfish::*;
+
+impl Snailfish {
+    pub fn pair(left: Snailfish, right: Snailfish) -> Snailfish {
+        Pair(Box::new(left), Box::new(right))
+    }
+
+    pub fn magnitude(&self) -> u32 {
+        match self {
+            Regular(n) => *n,
+            Pair(l, r) => 3 * l.magnitude() + 2 * r.magnitude(),
+        }
+    }
+
+    /// Applies explosions and splits until neither is possible. Explosions
+    /// always take priority over splits.
+    pub fn reduce(&mut self) {
+        while self.explode(0).is_some() || self.split() {}
+    }
+
+    /// Explodes the leftmost pair nested inside four pairs, returning the
+    /// values still to be added to the neighbours on either side.
+    pub fn explode(&mut self, depth: usize) -> Option<(Option<u32>, Option<u32>)> {
+        match self {
+            Regular(_) => None,
+            Pair(l, r) => {
+                if depth >= 4 {
+                    if let (Regular(a), Regular(b)) = (&**l, &**r) {
+                        let carry = (Some(*a), Some(*b));
+                        *self = Regular(0);
+                        return Some(carry);
+                    }
+                }
+                if let Some((a, b)) = l.explode(depth + 1) {
+                    if let Some(b) = b {
+                        r.add_leftmost(b);
+                    }
+                    return Some((a, None));
+                }
+                if let Some((a, b)) = r.explode(depth + 1) {
+                    if let Some(a) = a {
+                        l.add_rightmost(a);
+                    }
+                    return Some((None, b));
+                }
+                None
+            }
+        }
+    }
+
+    /// Splits the leftmost regular number of 10 or more.
+    pub fn split(&mut self) -> bool {
+        match self {
+            Regular(n) if *n >= 10 => {
+                *self = Snailfish::pair(Regular(*n / 2), Regular(n.div_ceil(2)));
+                true
+            }
+            Regular(_) => false,
+            Pair(l, r) => l.split() || r.split(),
+        }
+    }
+
+    fn add_leftmost(&mut self, value: u32) {
+        match self {
+            Regular(n) => *n += value,
+            Pair(l, _) => l.add_leftmost(value),
+        }
+    }
+
+    fn add_rightmost(&mut self, value: u32) {
+        match self {
+            Regular(n) => *n += value,
+            Pair(_, r) => r.add_rightmost(value),
+        }
+    }
+
+    /// Whether neither an explosion nor a split applies, as for every
+    /// number in the homework.
+    pub fn is_reduced(&self) -> bool {
+        self.reduced(0)
+    }
+
+    fn reduced(&self, depth: usize) -> bool {
+        match self {
+            Regular(n) => *n < 10,
+            Pair(l, r) => depth < 4 && l.reduced(depth + 1) && r.reduced(depth + 1),
+        }
+    }
+
+    fn from_pest(
+        pair: pest::iterators::Pair<Rule>,
+    ) -> Result<Snailfish, Box<pest::error::Error<Rule>>> {
+        match pair.as_rule() {
+            Rule::regular => match pair.as_str().parse() {
+                Ok(n) => Ok(Regular(n)),
+                Err(_) => Err(Box::new(pest::error::Error::new_from_span(
+                    pest::error::ErrorVariant::CustomError {
+                        message: "regular number too large".to_string(),
+                    },
+                    pair.as_span(),
+                ))),
+            },
+            Rule::pair => {
+                let mut inner = pair.into_inner();
+                let left = Snailfish::from_pest(inner.next().unwrap())?;
+                let right = Snailfish::from_pest(inner.next().unwrap())?;
+                Ok(Snailfish::pair(left, right))
+            }
+            rule => unreachable!("unexpected rule {:?}", rule),
+        }
+    }
+}
+
+impl Add for Snailfish {
+    type Output = Snailfish;
+
+    fn add(self, other: Snailfish) -> Snailfish {
+        let mut sum = Snailfish::pair(self, other);
+        sum.reduce();
+        sum
+    }
+}
+
+impl FromStr for Snailfish {
+    type Err = Box<pest::error::Error<Rule>>;
+
+    fn from_str(s: &str) -> Result<Snailfish, Self::Err> {
+        let line = SnailfishParser::parse(Rule::line, s)?.next().unwrap();
+        Snailfish::from_pest(line.into_inner().next().unwrap())
+    }
+}
+
+impl fmt::Display for Snailfish {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Regular(n) => write!(f, "{}", n),
+            Pair(l, r) => write!(f, "[{},{}]", l, r),
+        }
+    }
+}
+
+/// Parses one homework line, returning the column and description of the
+/// problem if it is not a reduced snailfish number.
+fn read_number(line: &str) -> Result<Snailfish, (usize, String)> {
+    let mut depth = 0;
+    for (i, c) in line.chars().enumerate() {
+        match c {
+            '[' => depth += 1,
+            ']' if depth == 0 => return Err((i + 1, "unmatched ']'".to_string())),
+            ']' => depth -= 1,
+            _ => {}
+        }
+    }
+    if depth > 0 {
+        let message = format!("{} unclosed '['", depth);
+        return Err((line.len() + 1, message));
+    }
+    let number = line.parse::<Snailfish>().map_err(|err| {
+        let column = match err.line_col {
+            pest::error::LineColLocation::Pos((_, c)) => c,
+            pest::error::LineColLocation::Span((_, c), _) => c,
+        };
+        let message = match &err.variant {
+            pest::error::ErrorVariant::ParsingError { positives, .. } => {
+                let rules = positives
+                    .iter()
+                    .map(|r| format!("{:?}", r))
+                    .collect::<Vec<_>>();
+                format!("expected {}", rules.join(" or "))
+            }
+            pest::error::ErrorVariant::CustomError { message } => message.clone(),
+        };
+        (column, message)
+    })?;
+    match number.is_reduced() {
+        true => Ok(number),
+        false => Err((1, "number is not reduced".to_string())),
+    }
+}
+
+pub fn read_numbers(read: &str) -> Result<Vec<Snailfish>, String> {
+    read.lines()
+        .enumerate()
+        .map(|(i, line)| {
+            read_number(line).map_err(|(column, message)| {
+                format!("line {}, column {}: {}", i + 1, column, message)
+            })
+        })
+        .collect()
+}
+
+/// Largest magnitude of `a + b` over all ordered pairs of distinct numbers,
+/// with the indices of the two addends.
+pub fn largest_pair_sum(numbers: &[Snailfish]) -> Option<(u32, (usize, usize))> {
+    let mut best: Option<(u32, (usize, usize))> = None;
+    for (i, a) in numbers.iter().enumerate() {
+        for (j, b) in numbers.iter().enumerate() {
+            if i == j {
+                continue;
+            }
+            let magnitude = (a.clone() + b.clone()).magnitude();
+            if best.map(|(m, _)| magnitude > m).unwrap_or(true) {
+                best = Some((magnitude, (i, j)));
+            }
+        }
+    }
+    best
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn number(s: &str) -> Snailfish {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn challenge1() {
+        let numbers = read_numbers(&input::read_file(&input::Key::example(18))).unwrap();
+        let sum = numbers.into_iter().reduce(|a, b| a + b).unwrap();
+        assert_eq!(
+            sum.to_string(),
+            "[[[[6,6],[7,6]],[[7,7],[7,0]]],[[[7,7],[7,7]],[[7,8],[9,9]]]]"
+        );
+        assert_eq!(sum.magnitude(), 4140);
+    }
+
+    #[test]
+    fn challenge2() {
+        let numbers = read_numbers(&input::read_file(&input::Key::example(18))).unwrap();
+        assert_eq!(largest_pair_sum(&numbers), Some((3993, (8, 0))));
+    }
+
+    #[test]
+    fn parse_works() {
+        let text = "[[1,2],[[3,4],5]]";
+        assert_eq!(number(text).to_string(), text);
+        assert_eq!(
+            number("[1,[2,3]]"),
+            Snailfish::pair(Regular(1), Snailfish::pair(Regular(2), Regular(3)))
+        );
+        assert!("[1,2".parse::<Snailfish>().is_err());
+        assert!("[1,2,3]".parse::<Snailfish>().is_err());
+        assert!("7".parse::<Snailfish>().is_err());
+        assert!("[1,99999999999]".parse::<Snailfish>().is_err());
+        assert_eq!(
+            read_numbers("[1,2]\n[[1,2]\n"),
+            Err("line 2, column 7: 1 unclosed '['".to_string())
+        );
+        assert_eq!(
+            read_numbers("[1,12]\n"),
+            Err("line 1, column 1: number is not reduced".to_string())
+        );
+        assert_eq!(
+            read_numbers("[1,2]]\n"),
+            Err("line 1, column 6: unmatched ']'".to_string())
+        );
+    }
+
+    #[test]
+    fn schema_works() {
+        assert!(schema().validate("[[1,2],[[3,4],5]]\n").is_empty());
+        assert_eq!(schema().validate("[[1,2]\n").len(), 1);
+        assert_eq!(schema().validate("[[[[[9,8],1],2],3],4]\n").len(), 1);
+    }
+
+    #[test]
+    fn explode_works() {
+        let cases = vec![
+            ("[[[[[9,8],1],2],3],4]", "[[[[0,9],2],3],4]"),
+            ("[7,[6,[5,[4,[3,2]]]]]", "[7,[6,[5,[7,0]]]]"),
+            ("[[6,[5,[4,[3,2]]]],1]", "[[6,[5,[7,0]]],3]"),
+            (
+                "[[3,[2,[1,[7,3]]]],[6,[5,[4,[3,2]]]]]",
+                "[[3,[2,[8,0]]],[9,[5,[4,[3,2]]]]]",
+            ),
+        ];
+        for (before, after) in cases {
+            let mut n = number(before);
+            assert!(n.explode(0).is_some());
+            assert_eq!(n.to_string(), after);
+        }
+    }
+
+    #[test]
+    fn add_works() {
+        let sum = number("[[[[4,3],4],4],[7,[[8,4],9]]]") + number("[1,1]");
+        assert_eq!(sum.to_string(), "[[[[0,7],4],[[7,8],[6,0]]],[8,1]]");
+        let mut n = Regular(11);
+        assert!(n.split());
+        assert_eq!(n.to_string(), "[5,6]");
+    }
+
+    #[test]
+    fn magnitude_works() {
+        assert_eq!(number("[[1,2],[[3,4],5]]").magnitude(), 143);
+        assert_eq!(
+            number("[[[[8,7],[7,7]],[[8,6],[7,7]]],[[[0,7],[6,6]],[8,7]]]").magnitude(),
+            3488
+        );
+    }
+}
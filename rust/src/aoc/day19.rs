@@ -0,0 +1,335 @@
+use crate::aoc::input;
+use crate::aoc::schema::{Schema, Section, Token};
+
+use std::collections::{BTreeSet, HashMap, VecDeque};
+
+pub fn main(key: &input::Key) {
+    let map = solve(&input::read_file(key));
+    println!("...1: {}", answer1(&map));
+    println!("...2: {}", answer2(&map));
+}
+
+pub fn challenge1(key: &input::Key) -> String {
+    answer1(&solve(&input::read_file(key)))
+}
+
+pub fn challenge2(key: &input::Key) -> String {
+    answer2(&solve(&input::read_file(key)))
+}
+
+fn solve(read: &str) -> Result<Map, String> {
+    reconstruct(&read_scanners(read)).ok_or_else(|| {
+        "Unable to reconstruct the map: some scanner overlaps with none of the others".to_string()
+    })
+}
+
+fn answer1(map: &Result<Map, String>) -> String {
+    match map {
+        Ok(map) => format!("Number of beacons: {}", map.beacons.len()),
+        Err(err) => err.clone(),
+    }
+}
+
+fn answer2(map: &Result<Map, String>) -> String {
+    match map {
+        Ok(map) => format!(
+            "Largest Manhattan distance between scanners: {}",
+            largest_distance(&map.scanners)
+        ),
+        Err(err) => err.clone(),
+    }
+}
+
+pub fn schema() -> Schema {
+    let coordinate = || Token::Number(-10_000, 10_000);
+    Schema::sections(vec![Section::new(
+        "scanner",
+        vec![Token::list(coordinate(), Token::Literal(","), 3, 3)],
+    )
+    .header(vec![
+        Token::Literal("--- scanner "),
+        Token::Number(0, i64::MAX),
+        Token::Literal(" ---"),
+    ])
+    .lines(2, usize::MAX)
+    .repeated()])
+}
+
+/// Beacons that two scanners must share to count as overlapping.
+pub const OVERLAP: usize = 12;
+
+pub type Point = [i32; 3];
+pub type Rotation = [[i32; 3]; 3];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Scanner {
+    pub id: usize,
+    pub beacons: Vec<Point>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Map {
+    pub beacons: BTreeSet<Point>,
+    /// Scanner positions relative to scanner 0, indexed like the input.
+    pub scanners: Vec<Point>,
+}
+
+pub fn read_scanners(read: &str) -> Vec<Scanner> {
+    read.split("\n\n")
+        .map(|report| {
+            let mut lines = report.lines();
+            let id = lines
+                .next()
+                .unwrap()
+                .trim_start_matches("--- scanner ")
+                .trim_end_matches(" ---")
+                .parse()
+                .unwrap();
+            let beacons = lines
+                .map(|l| {
+                    let c = l
+                        .split(',')
+                        .map(|v| v.parse().unwrap())
+                        .collect::<Vec<i32>>();
+                    [c[0], c[1], c[2]]
+                })
+                .collect();
+            Scanner { id, beacons }
+        })
+        .collect()
+}
+
+/// The 24 orientations: signed permutation matrices with determinant 1.
+pub fn rotations() -> Vec<Rotation> {
+    let permutations = [
+        [0, 1, 2],
+        [0, 2, 1],
+        [1, 0, 2],
+        [1, 2, 0],
+        [2, 0, 1],
+        [2, 1, 0],
+    ];
+    let mut rotations = vec![];
+    for p in permutations.iter() {
+        for signs in 0..8 {
+            let mut m = [[0; 3]; 3];
+            for row in 0..3 {
+                m[row][p[row]] = match signs >> row & 1 {
+                    0 => 1,
+                    _ => -1,
+                };
+            }
+            if determinant(&m) == 1 {
+                rotations.push(m);
+            }
+        }
+    }
+    rotations
+}
+
+fn determinant(m: &Rotation) -> i32 {
+    m[0][0] * (m[1][1] * m[2][2] - m[1][2] * m[2][1])
+        - m[0][1] * (m[1][0] * m[2][2] - m[1][2] * m[2][0])
+        + m[0][2] * (m[1][0] * m[2][1] - m[1][1] * m[2][0])
+}
+
+pub fn rotate(m: &Rotation, p: &Point) -> Point {
+    let mut r = [0; 3];
+    for (row, v) in r.iter_mut().enumerate() {
+        *v = (0..3).map(|c| m[row][c] * p[c]).sum();
+    }
+    r
+}
+
+fn add(a: &Point, b: &Point) -> Point {
+    [a[0] + b[0], a[1] + b[1], a[2] + b[2]]
+}
+
+fn sub(a: &Point, b: &Point) -> Point {
+    [a[0] - b[0], a[1] - b[1], a[2] - b[2]]
+}
+
+pub fn manhattan(a: &Point, b: &Point) -> i32 {
+    sub(a, b).iter().map(|v| v.abs()).sum()
+}
+
+/// Squared distances between all beacon pairs, which do not depend on the
+/// scanner's position or orientation.
+pub fn fingerprint(beacons: &[Point]) -> Vec<i64> {
+    let mut distances = vec![];
+    for (i, a) in beacons.iter().enumerate() {
+        for b in beacons[i + 1..].iter() {
+            distances.push(sub(a, b).iter().map(|&v| (v as i64).pow(2)).sum());
+        }
+    }
+    distances.sort_unstable();
+    distances
+}
+
+/// Number of values two sorted fingerprints have in common.
+fn shared(a: &[i64], b: &[i64]) -> usize {
+    let (mut i, mut j, mut count) = (0, 0, 0);
+    while i < a.len() && j < b.len() {
+        match a[i].cmp(&b[j]) {
+            std::cmp::Ordering::Less => i += 1,
+            std::cmp::Ordering::Greater => j += 1,
+            std::cmp::Ordering::Equal => {
+                count += 1;
+                i += 1;
+                j += 1;
+            }
+        }
+    }
+    count
+}
+
+/// Whether two scanners can share `OVERLAP` beacons at all: that many
+/// beacons have `OVERLAP * (OVERLAP - 1) / 2` pairwise distances in common.
+pub fn may_overlap(a: &[i64], b: &[i64]) -> bool {
+    shared(a, b) >= OVERLAP * (OVERLAP - 1) / 2
+}
+
+/// Finds the rotation and offset that map `beacons` onto `reference` with at
+/// least `OVERLAP` beacons in common.
+pub fn align(reference: &[Point], beacons: &[Point]) -> Option<(Rotation, Point)> {
+    for rotation in rotations() {
+        let rotated = beacons
+            .iter()
+            .map(|b| rotate(&rotation, b))
+            .collect::<Vec<_>>();
+        let mut offsets: HashMap<Point, usize> = HashMap::new();
+        for r in reference.iter() {
+            for b in rotated.iter() {
+                let offset = sub(r, b);
+                let count = offsets.entry(offset).or_insert(0);
+                *count += 1;
+                if *count >= OVERLAP {
+                    return Some((rotation, offset));
+                }
+            }
+        }
+    }
+    None
+}
+
+/// Aligns all scanners to scanner 0, breadth first from each newly placed
+/// scanner. Returns `None` if some scanner overlaps with none of the others.
+pub fn reconstruct(scanners: &[Scanner]) -> Option<Map> {
+    let fingerprints = scanners
+        .iter()
+        .map(|s| fingerprint(&s.beacons))
+        .collect::<Vec<_>>();
+    let mut placed: Vec<Option<(Point, Vec<Point>)>> = vec![None; scanners.len()];
+    placed[0] = Some(([0; 3], scanners[0].beacons.clone()));
+    let mut queue = VecDeque::from(vec![0]);
+    while let Some(i) = queue.pop_front() {
+        let reference = placed[i].as_ref().unwrap().1.clone();
+        for j in 0..scanners.len() {
+            if placed[j].is_some() || !may_overlap(&fingerprints[i], &fingerprints[j]) {
+                continue;
+            }
+            if let Some((rotation, offset)) = align(&reference, &scanners[j].beacons) {
+                let beacons = scanners[j]
+                    .beacons
+                    .iter()
+                    .map(|b| add(&rotate(&rotation, b), &offset))
+                    .collect();
+                placed[j] = Some((offset, beacons));
+                queue.push_back(j);
+            }
+        }
+    }
+    let placed = placed.into_iter().collect::<Option<Vec<_>>>()?;
+    Some(Map {
+        beacons: placed.iter().flat_map(|(_, b)| b.iter().copied()).collect(),
+        scanners: placed.iter().map(|(p, _)| *p).collect(),
+    })
+}
+
+pub fn largest_distance(scanners: &[Point]) -> i32 {
+    scanners
+        .iter()
+        .flat_map(|a| scanners.iter().map(move |b| manhattan(a, b)))
+        .max()
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn example() -> Vec<Scanner> {
+        read_scanners(&input::read_file(&input::Key::example(19)))
+    }
+
+    #[test]
+    fn challenge1() {
+        let map = reconstruct(&example()).unwrap();
+        assert_eq!(map.beacons.len(), 108);
+    }
+
+    #[test]
+    fn challenge2() {
+        let map = reconstruct(&example()).unwrap();
+        assert_eq!(
+            map.scanners,
+            vec![
+                [0, 0, 0],
+                [68, -1246, -43],
+                [1105, -1205, 1229],
+                [-92, -2380, -20],
+                [-20, -1133, 1061]
+            ]
+        );
+        assert_eq!(largest_distance(&map.scanners), 3621);
+    }
+
+    #[test]
+    fn rotations_work() {
+        let rotations = rotations();
+        assert_eq!(rotations.len(), 24);
+        let images = rotations
+            .iter()
+            .map(|r| rotate(r, &[1, 2, 3]))
+            .collect::<BTreeSet<_>>();
+        assert_eq!(images.len(), 24);
+        assert!(images.contains(&[1, 2, 3]));
+        assert!(images.contains(&[-2, 1, 3]));
+        assert!(!images.contains(&[-1, 2, 3]));
+    }
+
+    #[test]
+    fn align_works() {
+        let scanners = example();
+        let prints = scanners
+            .iter()
+            .map(|s| fingerprint(&s.beacons))
+            .collect::<Vec<_>>();
+        assert!(may_overlap(&prints[0], &prints[1]));
+        assert!(!may_overlap(&prints[0], &prints[3]));
+        let (_, offset) = align(&scanners[0].beacons, &scanners[1].beacons).unwrap();
+        assert_eq!(offset, [68, -1246, -43]);
+        assert!(align(&scanners[0].beacons, &scanners[3].beacons).is_none());
+    }
+
+    #[test]
+    fn unreachable_scanner() {
+        let key = input::Key::new(input::YEAR, 19, "disjoint");
+        input::provide_inline(
+            &key,
+            "--- scanner 0 ---\n1,2,3\n4,5,6\n\n--- scanner 1 ---\n7,8,9\n10,11,12\n",
+        );
+        let message =
+            "Unable to reconstruct the map: some scanner overlaps with none of the others";
+        assert_eq!(super::challenge1(&key), message);
+        assert_eq!(super::challenge2(&key), message);
+    }
+
+    #[test]
+    fn read_works() {
+        let scanners = read_scanners("--- scanner 0 ---\n-1,2,3\n\n--- scanner 1 ---\n4,-5,6\n");
+        assert_eq!(scanners.len(), 2);
+        assert_eq!(scanners[1].id, 1);
+        assert_eq!(scanners[0].beacons, vec![[-1, 2, 3]]);
+    }
+}
@@ -0,0 +1,190 @@
+use crate::aoc::input;
+use crate::aoc::schema::{Schema, Section, Token, Violation};
+
+pub fn main(key: &input::Key) {
+    println!("...1: {}", challenge1(key));
+    println!("...2: {}", challenge2(key));
+}
+
+pub fn challenge1(key: &input::Key) -> String {
+    let (algorithm, image) = read_trench_map(&input::read_file(key));
+    format!(
+        "Lit pixels after 2 enhancements: {}",
+        lit_after(&algorithm, image, 2)
+    )
+}
+
+pub fn challenge2(key: &input::Key) -> String {
+    let (algorithm, image) = read_trench_map(&input::read_file(key));
+    format!(
+        "Lit pixels after 50 enhancements: {}",
+        lit_after(&algorithm, image, 50)
+    )
+}
+
+pub fn schema() -> Schema {
+    Schema::sections(vec![
+        Section::new("algorithm", vec![Token::Chars("#.")]).lines(1, 1),
+        Section::new("image", vec![Token::Chars("#.")]).rectangular(),
+    ])
+    .check(algorithm_length)
+}
+
+fn algorithm_length(read: &str) -> Vec<Violation> {
+    match read.lines().next().map(|l| l.chars().count()) {
+        Some(512) | None => vec![],
+        Some(n) => vec![Violation::new(
+            1,
+            1,
+            &format!("algorithm has {} pixels, expected 512", n),
+        )],
+    }
+}
+
+pub type Algorithm = Vec<bool>;
+
+/// A finite window onto an infinite image. Every pixel outside the window
+/// has the colour of `background`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Image {
+    pub pixels: Vec<Vec<bool>>,
+    pub background: bool,
+}
+
+pub fn read_trench_map(read: &str) -> (Algorithm, Image) {
+    let (algorithm, image) = read.split_once("\n\n").unwrap();
+    let pixels = |l: &str| l.chars().map(|c| c == '#').collect::<Vec<_>>();
+    (
+        pixels(algorithm.trim_end()),
+        Image {
+            pixels: image.lines().map(pixels).collect(),
+            background: false,
+        },
+    )
+}
+
+impl Image {
+    pub fn get(&self, r: isize, c: isize) -> bool {
+        if r < 0 || c < 0 {
+            return self.background;
+        }
+        self.pixels
+            .get(r as usize)
+            .and_then(|row| row.get(c as usize))
+            .copied()
+            .unwrap_or(self.background)
+    }
+
+    /// Applies the algorithm once. The window grows by one pixel on every
+    /// side; the background flips whenever the algorithm maps an all-dark
+    /// (or all-lit) neighbourhood to the opposite colour.
+    pub fn enhance(&self, algorithm: &[bool]) -> Image {
+        let height = self.pixels.len() as isize;
+        let width = self.pixels.first().map(|r| r.len()).unwrap_or(0) as isize;
+        let pixels = (-1..height + 1)
+            .map(|r| {
+                (-1..width + 1)
+                    .map(|c| algorithm[self.index(r, c)])
+                    .collect()
+            })
+            .collect();
+        let background = match self.background {
+            true => algorithm[511],
+            false => algorithm[0],
+        };
+        Image { pixels, background }
+    }
+
+    fn index(&self, r: isize, c: isize) -> usize {
+        let mut index = 0;
+        for dr in -1..=1 {
+            for dc in -1..=1 {
+                index = index << 1 | self.get(r + dr, c + dc) as usize;
+            }
+        }
+        index
+    }
+
+    /// Number of lit pixels, or `None` if infinitely many are lit.
+    pub fn lit(&self) -> Option<usize> {
+        match self.background {
+            true => None,
+            false => Some(self.pixels.iter().flatten().filter(|&&p| p).count()),
+        }
+    }
+}
+
+/// All images produced by repeated enhancement, starting with `image`.
+pub fn enhancements<'a>(algorithm: &'a [bool], image: Image) -> impl Iterator<Item = Image> + 'a {
+    std::iter::successors(Some(image), move |image| Some(image.enhance(algorithm)))
+}
+
+pub fn lit_after(algorithm: &[bool], image: Image, steps: usize) -> usize {
+    enhancements(algorithm, image)
+        .nth(steps)
+        .unwrap()
+        .lit()
+        .expect("infinitely many pixels are lit")
+}
+
+pub fn format_image(image: &Image) -> String {
+    image
+        .pixels
+        .iter()
+        .map(|r| {
+            r.iter()
+                .map(|c| match c {
+                    true => "#",
+                    false => ".",
+                })
+                .collect::<String>()
+        })
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn example() -> (Algorithm, Image) {
+        read_trench_map(&input::read_file(&input::Key::example(20)))
+    }
+
+    #[test]
+    fn challenge1() {
+        let (algorithm, image) = example();
+        assert_eq!(lit_after(&algorithm, image, 2), 41);
+    }
+
+    #[test]
+    fn challenge2() {
+        let (algorithm, image) = example();
+        assert_eq!(lit_after(&algorithm, image, 50), 3714);
+    }
+
+    #[test]
+    fn enhance_flips_background() {
+        let (algorithm, image) = example();
+        let images = enhancements(&algorithm, image).take(3).collect::<Vec<_>>();
+        assert_eq!(
+            format_image(&images[1]),
+            "#..#..#\n#...###\n.#.###.\n.#...##\n#.###..\n##.####\n###.###"
+        );
+        assert!(images[1].background);
+        assert_eq!(images[1].lit(), None);
+        assert!(!images[2].background);
+        assert_eq!(images[2].pixels.len(), 9);
+    }
+
+    #[test]
+    fn enhance_keeps_dark_background() {
+        // Lights exactly the pixels whose own bit (16) is set.
+        let identity = (0..512).map(|i| i & 16 != 0).collect::<Vec<_>>();
+        let (_, image) = example();
+        let enhanced = image.enhance(&identity).enhance(&identity);
+        assert!(!enhanced.background);
+        assert_eq!(enhanced.lit(), image.lit());
+        assert_eq!(format_image(&enhanced).lines().nth(2), Some("..#..#..."));
+    }
+}
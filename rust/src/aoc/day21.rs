@@ -0,0 +1,253 @@
+use crate::aoc::input;
+use crate::aoc::schema::{Schema, Section, Token, Violation};
+
+use recap::Recap;
+use serde::Deserialize;
+use std::collections::HashMap;
+
+pub fn main(key: &input::Key) {
+    println!("...1: {}", challenge1(key));
+    println!("...2: {}", challenge2(key));
+}
+
+pub fn challenge1(key: &input::Key) -> String {
+    let positions = match read_positions(&input::read_file(key)) {
+        Ok(positions) => positions,
+        Err(err) => return format!("Invalid starting positions: {}", err),
+    };
+    format!(
+        "Losing score times die rolls: {}",
+        deterministic_game(positions)
+    )
+}
+
+pub fn challenge2(key: &input::Key) -> String {
+    let positions = match read_positions(&input::read_file(key)) {
+        Ok(positions) => positions,
+        Err(err) => return format!("Invalid starting positions: {}", err),
+    };
+    let wins = quantum_wins(positions);
+    format!(
+        "Universes won by the better player: {}",
+        wins.iter().max().unwrap()
+    )
+}
+
+pub fn schema() -> Schema {
+    Schema::sections(vec![Section::new(
+        "players",
+        vec![
+            Token::Literal("Player "),
+            Token::OneOf(&["1", "2"]),
+            Token::Literal(" starting position: "),
+            Token::Number(1, 10),
+        ],
+    )
+    .lines(2, 2)])
+    .check(distinct_players)
+}
+
+fn distinct_players(read: &str) -> Vec<Violation> {
+    let mut seen = [false; 2];
+    let mut violations = vec![];
+    for (i, line) in read.lines().enumerate() {
+        if let Ok(start) = line.parse::<Start>() {
+            if (1..=2).contains(&start.player) {
+                if seen[start.player - 1] {
+                    violations.push(Violation::new(
+                        i + 1,
+                        8,
+                        &format!("player {} appears twice", start.player),
+                    ));
+                }
+                seen[start.player - 1] = true;
+            }
+        }
+    }
+    violations
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Recap)]
+#[recap(regex = r"Player (?P<player>\d) starting position: (?P<position>\d+)")]
+pub struct Start {
+    player: usize,
+    position: usize,
+}
+
+pub fn read_positions(read: &str) -> Result<[usize; 2], String> {
+    let mut positions = [0; 2];
+    for (i, line) in read.lines().enumerate() {
+        let start: Start = line
+            .parse()
+            .map_err(|_| format!("line {}: invalid starting position {:?}", i + 1, line))?;
+        if !(1..=2).contains(&start.player) {
+            return Err(format!("line {}: unknown player {}", i + 1, start.player));
+        }
+        if !(1..=10).contains(&start.position) {
+            return Err(format!(
+                "line {}: position {} is off the board",
+                i + 1,
+                start.position
+            ));
+        }
+        if positions[start.player - 1] != 0 {
+            return Err(format!(
+                "line {}: player {} appears twice",
+                i + 1,
+                start.player
+            ));
+        }
+        positions[start.player - 1] = start.position;
+    }
+    match positions.iter().position(|p| *p == 0) {
+        Some(missing) => Err(format!("player {} is missing", missing + 1)),
+        None => Ok(positions),
+    }
+}
+
+fn advance(position: usize, steps: usize) -> usize {
+    (position + steps - 1) % 10 + 1
+}
+
+/// Plays to 1000 with a 100-sided die that rolls 1, 2, 3, ... and returns
+/// the losing score multiplied by the number of rolls.
+pub fn deterministic_game(mut positions: [usize; 2]) -> usize {
+    let mut scores = [0; 2];
+    let mut rolls = 0;
+    let mut player = 0;
+    loop {
+        let steps: usize = (0..3).map(|i| (rolls + i) % 100 + 1).sum();
+        rolls += 3;
+        positions[player] = advance(positions[player], steps);
+        scores[player] += positions[player];
+        if scores[player] >= 1000 {
+            return scores[1 - player] * rolls;
+        }
+        player = 1 - player;
+    }
+}
+
+/// How many of the 27 universes of three Dirac rolls move a given number of
+/// steps.
+pub fn roll_distribution() -> Vec<(usize, u64)> {
+    let mut counts: HashMap<usize, u64> = HashMap::new();
+    for a in 1..=3 {
+        for b in 1..=3 {
+            for c in 1..=3 {
+                *counts.entry(a + b + c).or_insert(0) += 1;
+            }
+        }
+    }
+    let mut distribution = counts.into_iter().collect::<Vec<_>>();
+    distribution.sort_unstable();
+    distribution
+}
+
+/// Positions, scores and the player about to move.
+type State = ([usize; 2], [usize; 2], usize);
+
+/// Number of universes in which each player reaches 21 first.
+pub fn quantum_wins(positions: [usize; 2]) -> [u64; 2] {
+    let mut memo = HashMap::new();
+    wins_from((positions, [0, 0], 0), &roll_distribution(), &mut memo)
+}
+
+fn wins_from(
+    state: State,
+    distribution: &[(usize, u64)],
+    memo: &mut HashMap<State, [u64; 2]>,
+) -> [u64; 2] {
+    if let Some(wins) = memo.get(&state) {
+        return *wins;
+    }
+    let (positions, scores, player) = state;
+    let mut wins = [0; 2];
+    for &(steps, universes) in distribution {
+        let mut positions = positions;
+        let mut scores = scores;
+        positions[player] = advance(positions[player], steps);
+        scores[player] += positions[player];
+        if scores[player] >= 21 {
+            wins[player] += universes;
+        } else {
+            let next = wins_from((positions, scores, 1 - player), distribution, memo);
+            wins[0] += universes * next[0];
+            wins[1] += universes * next[1];
+        }
+    }
+    memo.insert(state, wins);
+    wins
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn example() -> [usize; 2] {
+        read_positions(&input::read_file(&input::Key::example(21))).unwrap()
+    }
+
+    #[test]
+    fn challenge1() {
+        assert_eq!(deterministic_game(example()), 739785);
+    }
+
+    #[test]
+    fn challenge2() {
+        assert_eq!(quantum_wins(example()), [444356092776315, 341960390180808]);
+    }
+
+    #[test]
+    fn read_works() {
+        assert_eq!(example(), [4, 8]);
+        assert_eq!(
+            read_positions("Player 2 starting position: 10\nPlayer 1 starting position: 1\n"),
+            Ok([1, 10])
+        );
+        assert_eq!(
+            read_positions("Player 1 starting position: 4\n"),
+            Err("player 2 is missing".to_string())
+        );
+        assert_eq!(
+            read_positions("Player 1 starting position: 4\nPlayer 1 starting position: 8\n"),
+            Err("line 2: player 1 appears twice".to_string())
+        );
+        assert_eq!(
+            read_positions("Player 1 starting position: 4\nPlayer 2 starting position: 11\n"),
+            Err("line 2: position 11 is off the board".to_string())
+        );
+    }
+
+    #[test]
+    fn schema_works() {
+        let schema = schema();
+        assert!(schema
+            .validate("Player 1 starting position: 4\nPlayer 2 starting position: 8\n")
+            .is_empty());
+        assert_eq!(schema.validate("Player 1 starting position: 4\n").len(), 1);
+        assert_eq!(
+            schema
+                .validate("Player 2 starting position: 4\nPlayer 2 starting position: 8\n")
+                .len(),
+            1
+        );
+        assert_eq!(
+            schema
+                .validate("Player 1 starting position: 4\nPlayer 2 starting position: 0\n")
+                .len(),
+            1
+        );
+    }
+
+    #[test]
+    fn roll_distribution_works() {
+        let distribution = roll_distribution();
+        assert_eq!(
+            distribution,
+            vec![(3, 1), (4, 3), (5, 6), (6, 7), (7, 6), (8, 3), (9, 1)]
+        );
+        assert_eq!(distribution.iter().map(|d| d.1).sum::<u64>(), 27);
+        assert_eq!(advance(7, 5), 2);
+        assert_eq!(advance(4, 6), 10);
+    }
+}
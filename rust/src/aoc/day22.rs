@@ -0,0 +1,262 @@
+use crate::aoc::input;
+use crate::aoc::schema::{Schema, Token, Violation};
+
+use recap::Recap;
+use serde::Deserialize;
+
+pub fn main(key: &input::Key) {
+    println!("...1: {}", challenge1(key));
+    println!("...2: {}", challenge2(key));
+}
+
+pub fn challenge1(key: &input::Key) -> String {
+    let steps = match read_steps(&input::read_file(key)) {
+        Ok(steps) => steps,
+        Err(err) => return format!("Invalid reboot steps: {}", err),
+    };
+    let region = Cuboid::cube(-50, 50);
+    format!(
+        "Cubes on in the initialization region: {}",
+        cubes_on(&steps, Some(&region))
+    )
+}
+
+pub fn challenge2(key: &input::Key) -> String {
+    let steps = match read_steps(&input::read_file(key)) {
+        Ok(steps) => steps,
+        Err(err) => return format!("Invalid reboot steps: {}", err),
+    };
+    format!("Cubes on after reboot: {}", cubes_on(&steps, None))
+}
+
+pub fn schema() -> Schema {
+    let range = |axis| {
+        vec![
+            Token::Literal(axis),
+            Token::Number(-1_000_000, 1_000_000),
+            Token::Literal(".."),
+            Token::Number(-1_000_000, 1_000_000),
+        ]
+    };
+    let mut line = vec![Token::OneOf(&["on", "off"])];
+    line.append(&mut range(" x="));
+    line.append(&mut range(",y="));
+    line.append(&mut range(",z="));
+    Schema::lines(line).check(range_order)
+}
+
+/// Every range must run from its lower bound up to its upper one.
+fn range_order(read: &str) -> Vec<Violation> {
+    let mut violations = vec![];
+    for (i, line) in read.lines().enumerate() {
+        for (pos, _) in line.match_indices('=') {
+            let range = line[pos + 1..].split(',').next().unwrap();
+            let bounds = range
+                .split_once("..")
+                .map(|(lo, hi)| (lo.parse::<i64>(), hi.parse::<i64>()));
+            if let Some((Ok(lo), Ok(hi))) = bounds {
+                if lo > hi {
+                    let message = format!("range {} runs backwards", range);
+                    violations.push(Violation::new(i + 1, pos + 2, &message));
+                }
+            }
+        }
+    }
+    violations
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Power {
+    On,
+    Off,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Recap)]
+#[recap(
+    regex = r"(?P<power>on|off) x=(?P<x1>-?\d+)\.\.(?P<x2>-?\d+),y=(?P<y1>-?\d+)\.\.(?P<y2>-?\d+),z=(?P<z1>-?\d+)\.\.(?P<z2>-?\d+)"
+)]
+pub struct Step {
+    power: Power,
+    x1: i64,
+    x2: i64,
+    y1: i64,
+    y2: i64,
+    z1: i64,
+    z2: i64,
+}
+
+impl Step {
+    pub fn cuboid(&self) -> Cuboid {
+        Cuboid {
+            min: [self.x1, self.y1, self.z1],
+            max: [self.x2, self.y2, self.z2],
+        }
+    }
+}
+
+pub fn read_steps(read: &str) -> Result<Vec<Step>, String> {
+    read.lines()
+        .enumerate()
+        .map(|(i, line)| {
+            let step: Step = line
+                .parse()
+                .map_err(|_| format!("line {}: invalid reboot step {:?}", i + 1, line))?;
+            let cuboid = step.cuboid();
+            match (0..3).all(|a| cuboid.min[a] <= cuboid.max[a]) {
+                true => Ok(step),
+                false => Err(format!(
+                    "line {}: range runs backwards in {:?}",
+                    i + 1,
+                    line
+                )),
+            }
+        })
+        .collect()
+}
+
+/// An axis-aligned box of cubes, bounds inclusive.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cuboid {
+    pub min: [i64; 3],
+    pub max: [i64; 3],
+}
+
+impl Cuboid {
+    pub fn cube(min: i64, max: i64) -> Cuboid {
+        Cuboid {
+            min: [min; 3],
+            max: [max; 3],
+        }
+    }
+
+    pub fn intersection(&self, other: &Cuboid) -> Option<Cuboid> {
+        let mut cuboid = *self;
+        for axis in 0..3 {
+            cuboid.min[axis] = self.min[axis].max(other.min[axis]);
+            cuboid.max[axis] = self.max[axis].min(other.max[axis]);
+            if cuboid.min[axis] > cuboid.max[axis] {
+                return None;
+            }
+        }
+        Some(cuboid)
+    }
+
+    /// Volume as i128, as a cuboid spanning the whole schema range already
+    /// holds close to `i64::MAX` cubes.
+    pub fn volume(&self) -> i128 {
+        (0..3)
+            .map(|a| (self.max[a] - self.min[a] + 1) as i128)
+            .product()
+    }
+}
+
+/// Runs the steps, optionally restricted to `region`, and counts the cubes
+/// left on. Keeps a list of signed cuboids whose volumes sum to the lit
+/// volume: each step cancels its overlap with every listed cuboid, and an
+/// `on` step then adds itself.
+pub fn cubes_on(steps: &[Step], region: Option<&Cuboid>) -> u64 {
+    let mut signed: Vec<(Cuboid, i128)> = vec![];
+    for step in steps {
+        let cuboid = match region {
+            Some(region) => match step.cuboid().intersection(region) {
+                Some(cuboid) => cuboid,
+                None => continue,
+            },
+            None => step.cuboid(),
+        };
+        let overlaps = signed
+            .iter()
+            .filter_map(|(c, sign)| c.intersection(&cuboid).map(|i| (i, -sign)))
+            .collect::<Vec<_>>();
+        signed.extend(overlaps);
+        if step.power == Power::On {
+            signed.push((cuboid, 1));
+        }
+    }
+    signed
+        .iter()
+        .map(|(c, sign)| c.volume() * sign)
+        .sum::<i128>() as u64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn example() -> Vec<Step> {
+        read_steps(&input::read_file(&input::Key::example(22))).unwrap()
+    }
+
+    #[test]
+    fn challenge1() {
+        let steps = example();
+        let region = Cuboid::cube(-50, 50);
+        let counts = (1..=steps.len())
+            .map(|n| cubes_on(&steps[..n], Some(&region)))
+            .collect::<Vec<_>>();
+        assert_eq!(counts, vec![27, 46, 38, 39]);
+    }
+
+    #[test]
+    fn challenge2() {
+        let mut steps = example();
+        steps.push("on x=-60..60,y=0..0,z=0..0".parse().unwrap());
+        assert_eq!(cubes_on(&steps, Some(&Cuboid::cube(-50, 50))), 39 + 101);
+        assert_eq!(cubes_on(&steps, None), 39 + 121);
+        let key = input::Key::new(input::YEAR, 22, "example2");
+        let steps = read_steps(&input::read_file(&key)).unwrap();
+        assert_eq!(cubes_on(&steps, Some(&Cuboid::cube(-50, 50))), 474140);
+        assert_eq!(cubes_on(&steps, None), 2758514936282235);
+    }
+
+    #[test]
+    fn parse_works() {
+        assert_eq!(
+            "off x=-5..-3,y=0..1,z=2..2".parse::<Step>().unwrap(),
+            Step {
+                power: Power::Off,
+                x1: -5,
+                x2: -3,
+                y1: 0,
+                y2: 1,
+                z1: 2,
+                z2: 2
+            }
+        );
+        assert!("toggle x=1..2,y=1..2,z=1..2".parse::<Step>().is_err());
+        assert_eq!(
+            read_steps("on x=1..2,y=1..2,z=1..2\noff x=1..2,y=2..1,z=1..2\n"),
+            Err("line 2: range runs backwards in \"off x=1..2,y=2..1,z=1..2\"".to_string())
+        );
+        assert_eq!(
+            read_steps("on x=1..2\n"),
+            Err("line 1: invalid reboot step \"on x=1..2\"".to_string())
+        );
+    }
+
+    #[test]
+    fn cuboid_works() {
+        let a = Cuboid::cube(0, 2);
+        let b = Cuboid::cube(2, 5);
+        assert_eq!(a.volume(), 27);
+        assert_eq!(a.intersection(&b), Some(Cuboid::cube(2, 2)));
+        assert_eq!(a.intersection(&Cuboid::cube(3, 4)), None);
+        assert_eq!(
+            Cuboid::cube(-1_000_000, 1_000_000).volume(),
+            2_000_001i128.pow(3)
+        );
+    }
+
+    #[test]
+    fn schema_checks_range_order() {
+        assert_eq!(schema().validate("on x=1..5,y=2..2,z=-3..3\n"), vec![]);
+        assert_eq!(
+            schema().validate("on x=5..1,y=2..2,z=3..-3\n"),
+            vec![
+                Violation::new(1, 6, "range 5..1 runs backwards"),
+                Violation::new(1, 20, "range 3..-3 runs backwards"),
+            ]
+        );
+    }
+}
@@ -0,0 +1,340 @@
+use crate::aoc::input;
+use crate::aoc::schema::{Schema, Token, Violation};
+
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap};
+use std::fmt;
+
+pub fn main(key: &input::Key) {
+    let solution = solve(&input::read_file(key));
+    println!("...1: {}", answer1(&solution));
+    println!("...2: {}", challenge2(key));
+    if let Ok((_, path)) = solution {
+        println!("\n{}", format_solution(&path));
+    }
+}
+
+pub fn challenge1(key: &input::Key) -> String {
+    answer1(&solve(&input::read_file(key)))
+}
+
+pub fn challenge2(key: &input::Key) -> String {
+    let read = input::read_file(key);
+    if let Err(err) = Burrow::parse(&read) {
+        return format!("Invalid burrow: {}", err);
+    }
+    match solve(&unfold(&read)) {
+        Ok((energy, _)) => format!(
+            "Least energy to organize the unfolded amphipods: {}",
+            energy
+        ),
+        Err(err) => err,
+    }
+}
+
+type Solution = Result<(usize, Vec<(usize, Burrow)>), String>;
+
+fn solve(read: &str) -> Solution {
+    let burrow = Burrow::parse(read).map_err(|err| format!("Invalid burrow: {}", err))?;
+    organise(&burrow).ok_or_else(|| "The amphipods cannot be organized".to_string())
+}
+
+fn answer1(solution: &Solution) -> String {
+    match solution {
+        Ok((energy, _)) => format!("Least energy to organize the amphipods: {}", energy),
+        Err(err) => err.clone(),
+    }
+}
+
+pub fn schema() -> Schema {
+    Schema::lines(vec![Token::Chars("#. ABCD")]).check(shape)
+}
+
+fn shape(read: &str) -> Vec<Violation> {
+    match Burrow::parse(read) {
+        Ok(_) => vec![],
+        Err(err) => vec![Violation::new(1, 1, &err)],
+    }
+}
+
+pub const SPECIES: [u8; 4] = [b'A', b'B', b'C', b'D'];
+pub const EMPTY: u8 = b'.';
+pub const HALLWAY: usize = 11;
+/// Hallway cells right above the rooms, where amphipods never stop.
+pub const ENTRANCES: [usize; 4] = [2, 4, 6, 8];
+
+/// Hallway cells followed by the rooms, each listed from the top slot down.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Burrow {
+    pub cells: Vec<u8>,
+    pub depth: usize,
+}
+
+pub fn energy(species: u8) -> usize {
+    10usize.pow((species - b'A') as u32)
+}
+
+/// Inserts the two folded lines of the diagram below the first room line.
+pub fn unfold(read: &str) -> String {
+    let mut lines = read.lines().collect::<Vec<_>>();
+    lines.splice(3..3, ["  #D#C#B#A#", "  #D#B#A#C#"]);
+    lines.join("\n") + "\n"
+}
+
+impl Burrow {
+    /// Reads the diagram: a wall, the hallway, one line per room slot and a
+    /// closing wall.
+    pub fn parse(read: &str) -> Result<Burrow, String> {
+        let lines = read.lines().map(|l| l.as_bytes()).collect::<Vec<_>>();
+        if lines.len() < 4 {
+            return Err(format!("expected at least 4 lines, found {}", lines.len()));
+        }
+        let cell = |line: usize, column: usize| match lines[line].get(column) {
+            Some(&c) if c == EMPTY || SPECIES.contains(&c) => Ok(c),
+            _ => Err(format!(
+                "line {}: expected an amphipod or '.' in column {}",
+                line + 1,
+                column + 1
+            )),
+        };
+        let mut cells = (1..=HALLWAY)
+            .map(|column| cell(1, column))
+            .collect::<Result<Vec<_>, _>>()?;
+        let depth = lines.len() - 3;
+        for room in 0..SPECIES.len() {
+            for slot in 0..depth {
+                cells.push(cell(2 + slot, 3 + 2 * room)?);
+            }
+        }
+        for species in SPECIES {
+            let count = cells.iter().filter(|&&c| c == species).count();
+            if count != depth {
+                return Err(format!(
+                    "found {} amphipods of type {}, expected {}",
+                    count, species as char, depth
+                ));
+            }
+        }
+        Ok(Burrow { cells, depth })
+    }
+
+    fn cell(&self, room: usize, slot: usize) -> usize {
+        HALLWAY + room * self.depth + slot
+    }
+
+    fn room(&self, room: usize) -> &[u8] {
+        &self.cells[self.cell(room, 0)..self.cell(room, self.depth)]
+    }
+
+    pub fn is_organised(&self) -> bool {
+        (0..SPECIES.len()).all(|r| self.room(r).iter().all(|&s| s == SPECIES[r]))
+    }
+
+    /// Whether the hallway is free from `from` (exclusive) to `to`
+    /// (inclusive).
+    fn clear(&self, from: usize, to: usize) -> bool {
+        let path = match from < to {
+            true => from + 1..=to,
+            false => to..=from - 1,
+        };
+        self.cells[path].iter().all(|&c| c == EMPTY)
+    }
+
+    fn moved(&self, from: usize, to: usize, steps: usize) -> (usize, Burrow) {
+        let mut next = self.clone();
+        next.cells.swap(from, to);
+        (steps * energy(self.cells[from]), next)
+    }
+
+    /// Every legal single move with its energy: from a room into the
+    /// hallway, or from the hallway into the amphipod's own room once that
+    /// only holds its own species.
+    pub fn moves(&self) -> Vec<(usize, Burrow)> {
+        let mut moves = vec![];
+        for h in 0..HALLWAY {
+            let species = self.cells[h];
+            if species == EMPTY {
+                continue;
+            }
+            let room = (species - b'A') as usize;
+            let contents = self.room(room);
+            if contents.iter().any(|&c| c != EMPTY && c != species)
+                || !self.clear(h, ENTRANCES[room])
+            {
+                continue;
+            }
+            let slot = contents.iter().rposition(|&c| c == EMPTY).unwrap();
+            let steps = h.abs_diff(ENTRANCES[room]) + slot + 1;
+            moves.push(self.moved(h, self.cell(room, slot), steps));
+        }
+        for (room, &entrance) in ENTRANCES.iter().enumerate() {
+            let contents = self.room(room);
+            let slot = match contents.iter().position(|&c| c != EMPTY) {
+                Some(slot) => slot,
+                None => continue,
+            };
+            if contents[slot..].iter().all(|&c| c == SPECIES[room]) {
+                continue;
+            }
+            for h in (0..HALLWAY).filter(|h| !ENTRANCES.contains(h)) {
+                if self.clear(entrance, h) {
+                    let steps = slot + 1 + h.abs_diff(entrance);
+                    moves.push(self.moved(self.cell(room, slot), h, steps));
+                }
+            }
+        }
+        moves
+    }
+}
+
+impl fmt::Display for Burrow {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "#############")?;
+        writeln!(f, "#{}#", String::from_utf8_lossy(&self.cells[..HALLWAY]))?;
+        for slot in 0..self.depth {
+            let row = (0..SPECIES.len())
+                .map(|r| (self.cells[self.cell(r, slot)] as char).to_string())
+                .collect::<Vec<_>>()
+                .join("#");
+            match slot {
+                0 => writeln!(f, "###{}###", row)?,
+                _ => writeln!(f, "  #{}#", row)?,
+            }
+        }
+        write!(f, "  #########")
+    }
+}
+
+/// Least total energy to organise the burrow, with the burrow after each
+/// move and the energy spent so far, found with Dijkstra's algorithm.
+pub fn organise(burrow: &Burrow) -> Option<(usize, Vec<(usize, Burrow)>)> {
+    let mut best: HashMap<Burrow, usize> = HashMap::new();
+    let mut previous: HashMap<Burrow, Burrow> = HashMap::new();
+    let mut queue = BinaryHeap::new();
+    best.insert(burrow.clone(), 0);
+    queue.push(Reverse((0, burrow.clone())));
+    while let Some(Reverse((spent, current))) = queue.pop() {
+        if current.is_organised() {
+            let mut path = vec![(spent, current.clone())];
+            let mut at = current;
+            while let Some(before) = previous.get(&at) {
+                path.push((best[before], before.clone()));
+                at = before.clone();
+            }
+            path.reverse();
+            return Some((spent, path));
+        }
+        if best.get(&current).map(|&b| spent > b).unwrap_or(false) {
+            continue;
+        }
+        for (cost, next) in current.moves() {
+            let total = spent + cost;
+            if best.get(&next).map(|&b| total < b).unwrap_or(true) {
+                best.insert(next.clone(), total);
+                previous.insert(next.clone(), current.clone());
+                queue.push(Reverse((total, next)));
+            }
+        }
+    }
+    None
+}
+
+pub fn format_solution(path: &[(usize, Burrow)]) -> String {
+    path.iter()
+        .map(|(spent, burrow)| format!("Energy {}:\n{}", spent, burrow))
+        .collect::<Vec<_>>()
+        .join("\n\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn example() -> String {
+        input::read_file(&input::Key::example(23))
+    }
+
+    #[test]
+    fn challenge1() {
+        let (energy, path) = organise(&Burrow::parse(&example()).unwrap()).unwrap();
+        assert_eq!(energy, 12521);
+        assert!(path.last().unwrap().1.is_organised());
+        for step in path.windows(2) {
+            let (before, after) = (&step[0], &step[1]);
+            assert!(before
+                .1
+                .moves()
+                .contains(&(after.0 - before.0, after.1.clone())));
+        }
+    }
+
+    #[test]
+    fn challenge2() {
+        let (energy, _) = organise(&Burrow::parse(&unfold(&example())).unwrap()).unwrap();
+        assert_eq!(energy, 44169);
+    }
+
+    #[test]
+    fn parse_works() {
+        let burrow = Burrow::parse(&example()).unwrap();
+        assert_eq!(burrow.depth, 2);
+        assert_eq!(burrow.to_string() + "\n", example());
+        let unfolded = Burrow::parse(&unfold(&example())).unwrap();
+        assert_eq!(unfolded.depth, 4);
+        assert_eq!(unfolded.to_string().lines().nth(3), Some("  #D#C#B#A#"));
+    }
+
+    #[test]
+    fn invalid_burrows() {
+        assert_eq!(
+            Burrow::parse("#\n#\n#AABBCCDD\n#\n#\n"),
+            Err("line 2: expected an amphipod or '.' in column 2".to_string())
+        );
+        assert_eq!(
+            Burrow::parse("#############\n#...........#\n"),
+            Err("expected at least 4 lines, found 2".to_string())
+        );
+        assert_eq!(schema().validate("#\n#\n#AABBCCDD\n#\n#\n").len(), 1);
+        let key = input::Key::new(input::YEAR, 23, "malformed");
+        input::provide_inline(&key, "#\n#\n");
+        assert_eq!(
+            super::challenge2(&key),
+            "Invalid burrow: expected at least 4 lines, found 2"
+        );
+        let key = input::Key::new(input::YEAR, 23, "crowded");
+        input::provide_inline(
+            &key,
+            "#############\n#...........#\n###A#A#A#A###\n  #A#A#A#A#\n  #########\n",
+        );
+        assert_eq!(
+            super::challenge1(&key),
+            "Invalid burrow: found 8 amphipods of type A, expected 2"
+        );
+    }
+
+    #[test]
+    fn moves_work() {
+        let burrow = Burrow::parse(&example()).unwrap();
+        // Each room's top amphipod can reach all seven stopping cells.
+        assert_eq!(burrow.moves().len(), 28);
+        let solved = Burrow::parse(
+            "#############\n#...........#\n###A#B#C#D###\n  #A#B#C#D#\n  #########\n",
+        )
+        .unwrap();
+        assert!(solved.is_organised());
+        assert_eq!(solved.moves(), vec![]);
+        let last = Burrow::parse(
+            "#############\n#.........A.#\n###.#B#C#D###\n  #A#B#C#D#\n  #########\n",
+        )
+        .unwrap();
+        assert_eq!(last.moves(), vec![(8, solved)]);
+    }
+
+    #[test]
+    fn format_solution_works() {
+        let (_, path) = organise(&Burrow::parse(&example()).unwrap()).unwrap();
+        let text = format_solution(&path[..2]);
+        assert!(text.starts_with("Energy 0:\n#############\n#...........#\n"));
+        assert!(text.contains("\n\nEnergy 2000:\n#############\n#.........D.#\n"));
+    }
+}
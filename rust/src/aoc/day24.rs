@@ -0,0 +1,418 @@
+use crate::aoc::input;
+use crate::aoc::schema::{Schema, Token, Violation};
+
+use std::fmt;
+use std::str::FromStr;
+
+pub fn main(key: &input::Key) {
+    println!("...1: {}", challenge1(key));
+    println!("...2: {}", challenge2(key));
+}
+
+pub fn challenge1(key: &input::Key) -> String {
+    solve(key, Extreme::Largest)
+}
+
+pub fn challenge2(key: &input::Key) -> String {
+    solve(key, Extreme::Smallest)
+}
+
+fn solve(key: &input::Key, extreme: Extreme) -> String {
+    let program = match read_program(&input::read_file(key)) {
+        Ok(program) => program,
+        Err(err) => return format!("Invalid program: {}", err),
+    };
+    let name = match extreme {
+        Extreme::Largest => "Largest",
+        Extreme::Smallest => "Smallest",
+    };
+    match verified_model_number(&program, extreme) {
+        Some(number) => format!("{} valid model number: {}", name, number),
+        None => "No valid model number: the program is not a MONAD".to_string(),
+    }
+}
+
+pub fn schema() -> Schema {
+    Schema::lines(vec![
+        Token::OneOf(&["inp", "add", "mul", "div", "mod", "eql"]),
+        Token::Literal(" "),
+        Token::OneOf(&["w", "x", "y", "z"]),
+        Token::optional(Token::Literal(" ")),
+        Token::optional(Token::Chars("wxyz-0123456789")),
+    ])
+    .check(instructions)
+}
+
+/// The tokens allow any operand after any instruction; the parser knows
+/// which instructions take a second one and what it may be.
+fn instructions(read: &str) -> Vec<Violation> {
+    read.lines()
+        .enumerate()
+        .filter_map(|(i, l)| match l.parse::<Instruction>() {
+            Ok(_) => None,
+            Err(err) => Some(Violation::new(i + 1, 1, &err)),
+        })
+        .collect()
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Operand {
+    Register(usize),
+    Value(i64),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Instruction {
+    Inp(usize),
+    Add(usize, Operand),
+    Mul(usize, Operand),
+    Div(usize, Operand),
+    Mod(usize, Operand),
+    Eql(usize, Operand),
+}
+
+use Instruction::*;
+
+fn register(name: &str) -> Result<usize, String> {
+    match name {
+        "w" => Ok(0),
+        "x" => Ok(1),
+        "y" => Ok(2),
+        "z" => Ok(3),
+        _ => Err(format!("unknown register {:?}", name)),
+    }
+}
+
+impl FromStr for Operand {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Operand, String> {
+        match s.parse() {
+            Ok(value) => Ok(Operand::Value(value)),
+            Err(_) => register(s).map(Operand::Register),
+        }
+    }
+}
+
+impl FromStr for Instruction {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Instruction, String> {
+        let parts = s.split(' ').collect::<Vec<_>>();
+        let operand = || -> Result<Operand, String> {
+            parts
+                .get(2)
+                .ok_or_else(|| format!("missing operand in {:?}", s))?
+                .parse()
+        };
+        let operands = match parts[0] {
+            "inp" => 1,
+            "add" | "mul" | "div" | "mod" | "eql" => 2,
+            op => return Err(format!("unknown instruction {:?}", op)),
+        };
+        if parts.len() != operands + 1 {
+            return Err(format!(
+                "{} takes {} operand(s), found {} in {:?}",
+                parts[0],
+                operands,
+                parts.len() - 1,
+                s
+            ));
+        }
+        let a = register(parts[1])?;
+        match parts[0] {
+            "inp" => Ok(Inp(a)),
+            "add" => Ok(Add(a, operand()?)),
+            "mul" => Ok(Mul(a, operand()?)),
+            "div" => Ok(Div(a, operand()?)),
+            "mod" => Ok(Mod(a, operand()?)),
+            _ => Ok(Eql(a, operand()?)),
+        }
+    }
+}
+
+pub fn read_program(read: &str) -> Result<Vec<Instruction>, String> {
+    read.lines()
+        .enumerate()
+        .map(|(i, l)| l.parse().map_err(|e| format!("line {}: {}", i + 1, e)))
+        .collect()
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AluError {
+    InputExhausted,
+    DivisionByZero,
+    InvalidModulo,
+    Overflow,
+}
+
+impl fmt::Display for AluError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let text = match self {
+            AluError::InputExhausted => "inp with no input left",
+            AluError::DivisionByZero => "div by zero",
+            AluError::InvalidModulo => "mod of a negative number or by a non-positive one",
+            AluError::Overflow => "result does not fit in a register",
+        };
+        write!(f, "{}", text)
+    }
+}
+
+/// Runs the program and returns the final `w`, `x`, `y` and `z`.
+pub fn run(program: &[Instruction], input: &[i64]) -> Result<[i64; 4], AluError> {
+    let mut registers = [0; 4];
+    let mut input = input.iter();
+    for instruction in program {
+        let value = |b: &Operand| match b {
+            Operand::Register(r) => registers[*r],
+            Operand::Value(v) => *v,
+        };
+        match instruction {
+            Inp(a) => registers[*a] = *input.next().ok_or(AluError::InputExhausted)?,
+            Add(a, b) => {
+                registers[*a] = registers[*a]
+                    .checked_add(value(b))
+                    .ok_or(AluError::Overflow)?
+            }
+            Mul(a, b) => {
+                registers[*a] = registers[*a]
+                    .checked_mul(value(b))
+                    .ok_or(AluError::Overflow)?
+            }
+            Div(a, b) => match value(b) {
+                0 => return Err(AluError::DivisionByZero),
+                b => registers[*a] = registers[*a].checked_div(b).ok_or(AluError::Overflow)?,
+            },
+            Mod(a, b) => match (registers[*a], value(b)) {
+                (a_value, b) if a_value < 0 || b <= 0 => {
+                    return Err(AluError::InvalidModulo);
+                }
+                (_, b) => registers[*a] %= b,
+            },
+            Eql(a, b) => registers[*a] = (registers[*a] == value(b)) as i64,
+        }
+    }
+    Ok(registers)
+}
+
+/// The parameters that vary between the 14 digit blocks of MONAD. `z` is
+/// used as a base-26 stack: a block with `div z 1` pushes `w + offset`, a
+/// block with `div z 26` pops the top and pushes again unless
+/// `w == top + check`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Block {
+    pub pops: bool,
+    pub check: i64,
+    pub offset: i64,
+}
+
+const BLOCK: [&str; 18] = [
+    "inp w", "mul x 0", "add x z", "mod x 26", "div z _", "add x _", "eql x w", "eql x 0",
+    "mul y 0", "add y 25", "mul y x", "add y 1", "mul z y", "mul y 0", "add y w", "add y _",
+    "mul y x", "add z y",
+];
+
+/// Splits MONAD into its digit blocks, or `None` if the program does not
+/// follow the block template.
+pub fn blocks(program: &[Instruction]) -> Option<Vec<Block>> {
+    if program.len() != 14 * BLOCK.len() {
+        return None;
+    }
+    let template = BLOCK
+        .iter()
+        .map(|l| l.replace('_', "0").parse::<Instruction>().unwrap())
+        .collect::<Vec<_>>();
+    program
+        .chunks(BLOCK.len())
+        .map(|chunk| {
+            let mut params = vec![];
+            for (instruction, expected) in chunk.iter().zip(template.iter()) {
+                match (instruction, expected) {
+                    (Div(3, Operand::Value(v)), Div(3, _))
+                    | (Add(1, Operand::Value(v)), Add(1, Operand::Value(0)))
+                    | (Add(2, Operand::Value(v)), Add(2, Operand::Value(0))) => params.push(*v),
+                    (i, e) if i == e => {}
+                    _ => return None,
+                }
+            }
+            let block = match params[..] {
+                [1, check, offset] => Block {
+                    pops: false,
+                    check,
+                    offset,
+                },
+                [26, check, offset] => Block {
+                    pops: true,
+                    check,
+                    offset,
+                },
+                _ => return None,
+            };
+            // A push block must never match its check for the stack view to hold.
+            match block.pops || block.check > 9 {
+                true => Some(block),
+                false => None,
+            }
+        })
+        .collect()
+}
+
+/// Pairs each pushing digit `i` with the digit `j` that pops it, such that
+/// `z` ends at 0 exactly when `digit[j] == digit[i] + difference` for every
+/// pair.
+pub fn constraints(blocks: &[Block]) -> Option<Vec<(usize, usize, i64)>> {
+    let mut stack = vec![];
+    let mut pairs = vec![];
+    for (j, block) in blocks.iter().enumerate() {
+        match block.pops {
+            false => stack.push(j),
+            true => {
+                let i = stack.pop()?;
+                pairs.push((i, j, blocks[i].offset + block.check));
+            }
+        }
+    }
+    match stack.is_empty() {
+        true => Some(pairs),
+        false => None,
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Extreme {
+    Largest,
+    Smallest,
+}
+
+/// Picks each constrained pair independently, pushing the earlier digit as
+/// high (or low) as the difference allows.
+pub fn model_number(pairs: &[(usize, usize, i64)], extreme: Extreme) -> Option<Vec<i64>> {
+    let mut digits = vec![0; pairs.len() * 2];
+    for &(i, j, difference) in pairs {
+        if difference.abs() > 8 {
+            return None;
+        }
+        digits[i] = match extreme {
+            Extreme::Largest => 9.min(9 - difference),
+            Extreme::Smallest => 1.max(1 - difference),
+        };
+        digits[j] = digits[i] + difference;
+    }
+    Some(digits)
+}
+
+/// Derives the model number from the block structure and confirms it by
+/// running MONAD on it.
+pub fn verified_model_number(program: &[Instruction], extreme: Extreme) -> Option<u64> {
+    let pairs = constraints(&blocks(program)?)?;
+    let digits = model_number(&pairs, extreme)?;
+    match run(program, &digits) {
+        Ok([_, _, _, 0]) => Some(digits.iter().fold(0, |n, d| n * 10 + *d as u64)),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn example() -> Vec<Instruction> {
+        read_program(&input::read_file(&input::Key::example(24))).unwrap()
+    }
+
+    fn digits(n: u64) -> Vec<i64> {
+        n.to_string()
+            .chars()
+            .map(|c| c.to_digit(10).unwrap() as i64)
+            .collect()
+    }
+
+    #[test]
+    fn challenge1() {
+        let program = example();
+        let largest = verified_model_number(&program, Extreme::Largest).unwrap();
+        assert_eq!(largest, 99799519189379);
+        // Changing one digit of a pair breaks the constraint.
+        assert_ne!(run(&program, &digits(99799519189389)).unwrap()[3], 0);
+    }
+
+    #[test]
+    fn challenge2() {
+        let program = example();
+        let smallest = verified_model_number(&program, Extreme::Smallest).unwrap();
+        assert_eq!(smallest, 73195119112113);
+        assert_eq!(run(&program, &digits(smallest)).unwrap()[3], 0);
+    }
+
+    #[test]
+    fn run_works() {
+        let negate = read_program("inp x\nmul x -1").unwrap();
+        assert_eq!(run(&negate, &[7]).unwrap()[1], -7);
+        let triple = read_program("inp z\ninp x\nmul z 3\neql z x").unwrap();
+        assert_eq!(run(&triple, &[2, 6]).unwrap()[3], 1);
+        assert_eq!(run(&triple, &[2, 7]).unwrap()[3], 0);
+        let bits = read_program(
+            "inp w\nadd z w\nmod z 2\ndiv w 2\nadd y w\nmod y 2\ndiv w 2\nadd x w\nmod x 2\ndiv w 2\nmod w 2",
+        )
+        .unwrap();
+        assert_eq!(run(&bits, &[13]).unwrap(), [1, 1, 0, 1]);
+        assert_eq!(run(&triple, &[2]), Err(AluError::InputExhausted));
+        let divide = read_program("inp w\ndiv x w").unwrap();
+        assert_eq!(run(&divide, &[0]), Err(AluError::DivisionByZero));
+        let square = read_program("inp w\nmul w w\nmul w w").unwrap();
+        assert_eq!(run(&square, &[1 << 20]), Err(AluError::Overflow));
+    }
+
+    #[test]
+    fn parse_works() {
+        assert_eq!(
+            "add x -12".parse::<Instruction>(),
+            Ok(Add(1, Operand::Value(-12)))
+        );
+        assert_eq!(
+            "eql x w".parse::<Instruction>(),
+            Ok(Eql(1, Operand::Register(0)))
+        );
+        assert!("inp q".parse::<Instruction>().is_err());
+        assert!("add x".parse::<Instruction>().is_err());
+        assert_eq!(
+            read_program("inp w\nsub x 1"),
+            Err("line 2: unknown instruction \"sub\"".to_string())
+        );
+        assert_eq!(
+            "inp w 3".parse::<Instruction>(),
+            Err("inp takes 1 operand(s), found 2 in \"inp w 3\"".to_string())
+        );
+    }
+
+    #[test]
+    fn schema_works() {
+        assert!(schema().validate("inp w\nadd x -12\n").is_empty());
+        assert_eq!(schema().validate("inp w 3\n").len(), 1);
+        assert_eq!(schema().validate("add x\n").len(), 1);
+    }
+
+    #[test]
+    fn invalid_input() {
+        let key = input::Key::new(input::YEAR, 24, "invalid");
+        input::provide_inline(&key, "inp w\nmul w 0\n");
+        assert_eq!(
+            super::challenge1(&key),
+            "No valid model number: the program is not a MONAD"
+        );
+        let key = input::Key::new(input::YEAR, 24, "malformed");
+        input::provide_inline(&key, "inp w 3\n");
+        assert!(super::challenge2(&key).starts_with("Invalid program: line 1: "));
+    }
+
+    #[test]
+    fn blocks_work() {
+        let program = example();
+        let blocks = blocks(&program).unwrap();
+        assert_eq!(blocks.len(), 14);
+        assert_eq!(blocks.iter().filter(|b| b.pops).count(), 7);
+        let pairs = constraints(&blocks).unwrap();
+        assert_eq!(pairs.len(), 7);
+        assert!(super::blocks(&program[..18]).is_none());
+    }
+}
@@ -0,0 +1,186 @@
+use crate::aoc::input;
+use crate::aoc::schema::Schema;
+
+use std::fmt;
+
+pub fn main(key: &input::Key) {
+    println!("...1: {}", challenge1(key));
+    println!("...2: {}", challenge2(key));
+}
+
+pub fn challenge1(key: &input::Key) -> String {
+    let mut floor = SeaFloor::parse(&input::read_file(key));
+    match floor.settle(MAX_STEPS, |_, _| {}) {
+        Some(steps) => format!("First step on which no sea cucumber moves: {}", steps),
+        None => format!("Sea cucumbers still moving after {} steps", MAX_STEPS),
+    }
+}
+
+pub fn challenge2(key: &input::Key) -> String {
+    let mut floor = SeaFloor::parse(&input::read_file(key));
+    match floor.settle(MAX_STEPS, |_, _| {}) {
+        Some(_) => format!("Sea cucumbers after they stop moving:\n{}", floor),
+        None => format!("Sea cucumbers still moving after {} steps", MAX_STEPS),
+    }
+}
+
+pub fn schema() -> Schema {
+    Schema::grid(".>v")
+}
+
+pub const EAST: u8 = b'>';
+pub const SOUTH: u8 = b'v';
+pub const EMPTY: u8 = b'.';
+
+/// Steps to wait for the sea cucumbers to stop. Some maps never settle, like
+/// a full row of east-facing ones with a single gap.
+pub const MAX_STEPS: usize = 100_000;
+
+/// Sea cucumbers on a map that wraps around at both edges.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SeaFloor {
+    pub cells: Vec<Vec<u8>>,
+}
+
+impl SeaFloor {
+    pub fn parse(read: &str) -> SeaFloor {
+        SeaFloor {
+            cells: read.lines().map(|l| l.as_bytes().to_vec()).collect(),
+        }
+    }
+
+    /// Moves one herd. All sea cucumbers of the herd look at the map as it
+    /// was before any of them moved.
+    fn move_herd(&mut self, herd: u8) -> usize {
+        let height = self.cells.len();
+        let width = self.cells[0].len();
+        let (dr, dc) = match herd {
+            EAST => (0, 1),
+            _ => (1, 0),
+        };
+        let moves = (0..height)
+            .flat_map(|r| (0..width).map(move |c| (r, c)))
+            .filter(|&(r, c)| self.cells[r][c] == herd)
+            .map(|(r, c)| ((r, c), ((r + dr) % height, (c + dc) % width)))
+            .filter(|&(_, (r, c))| self.cells[r][c] == EMPTY)
+            .collect::<Vec<_>>();
+        for &((r, c), (to_r, to_c)) in moves.iter() {
+            self.cells[r][c] = EMPTY;
+            self.cells[to_r][to_c] = herd;
+        }
+        moves.len()
+    }
+
+    /// One step: the east-facing herd moves, then the south-facing herd.
+    /// Returns how many sea cucumbers moved.
+    pub fn step(&mut self) -> usize {
+        self.move_herd(EAST) + self.move_herd(SOUTH)
+    }
+
+    /// Steps until nothing moves, calling `hook` with the step number and
+    /// the map after every step. Returns the first step without movement, or
+    /// `None` if there is still movement after `limit` steps.
+    pub fn settle<F>(&mut self, limit: usize, mut hook: F) -> Option<usize>
+    where
+        F: FnMut(usize, &SeaFloor),
+    {
+        for steps in 1..=limit {
+            let moved = self.step();
+            hook(steps, self);
+            if moved == 0 {
+                return Some(steps);
+            }
+        }
+        None
+    }
+}
+
+impl fmt::Display for SeaFloor {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let rows = self
+            .cells
+            .iter()
+            .map(|r| String::from_utf8_lossy(r).into_owned())
+            .collect::<Vec<_>>();
+        write!(f, "{}", rows.join("\n"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn example() -> SeaFloor {
+        SeaFloor::parse(&input::read_file(&input::Key::example(25)))
+    }
+
+    #[test]
+    fn challenge1() {
+        let mut frames = vec![];
+        let steps = example().settle(MAX_STEPS, |step, floor| {
+            frames.push((step, floor.to_string()))
+        });
+        assert_eq!(steps, Some(58));
+        assert_eq!(frames.len(), 58);
+        assert_eq!(frames[56].1, frames[57].1);
+        assert_ne!(frames[55].1, frames[56].1);
+    }
+
+    #[test]
+    fn challenge2() {
+        let mut floor = example();
+        assert_eq!(floor.settle(58, |_, _| {}), Some(58));
+        assert_eq!(
+            floor.to_string(),
+            [
+                "..>>v>vv..",
+                "..v.>>vv..",
+                "..>>v>>vv.",
+                "..>>>>>vv.",
+                "v......>vv",
+                "v>v....>>v",
+                "vvv.....>>",
+                ">vv......>",
+                ".>v.vv.v..",
+            ]
+            .join("\n")
+        );
+    }
+
+    #[test]
+    fn step_works() {
+        let mut floor = SeaFloor::parse("...>>>>>...\n");
+        floor.step();
+        assert_eq!(floor.to_string(), "...>>>>.>..");
+        floor.step();
+        assert_eq!(floor.to_string(), "...>>>.>.>.");
+
+        let mut floor = SeaFloor::parse("..........\n.>v....v..\n.......>..\n..........\n");
+        floor.step();
+        assert_eq!(
+            floor.to_string(),
+            "..........\n.>........\n..v....v>.\n.........."
+        );
+
+        // East-facing sea cucumbers wrap to the west edge, then block the
+        // south-facing one that wanted that cell.
+        let mut floor = SeaFloor::parse("v..\n..>\n");
+        floor.step();
+        assert_eq!(floor.to_string(), "v..\n>..");
+    }
+
+    #[test]
+    fn settle_gives_up() {
+        assert_eq!(example().settle(57, |_, _| {}), None);
+        let mut floor = SeaFloor::parse(">>>>.\n");
+        let mut steps = 0;
+        assert_eq!(floor.settle(1000, |step, _| steps = step), None);
+        assert_eq!(steps, 1000);
+        let key = input::Key::new(input::YEAR, 25, "restless");
+        input::provide_inline(&key, ">>>>.\n");
+        assert_eq!(
+            super::challenge1(&key),
+            format!("Sea cucumbers still moving after {} steps", MAX_STEPS)
+        );
+    }
+}
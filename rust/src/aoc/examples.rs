@@ -0,0 +1,213 @@
+use crate::aoc::input;
+use crate::aoc::registry;
+
+/// Where an example's input comes from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Source {
+    Profile(String),
+    Inline(String),
+}
+
+/// One row of `inputs/<year>/examples.txt`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Example {
+    pub line: usize,
+    pub day: usize,
+    pub part: usize,
+    pub source: Source,
+    pub expected: String,
+}
+
+impl Example {
+    pub fn key(&self, year: usize) -> input::Key {
+        match &self.source {
+            Source::Profile(profile) => input::Key::new(year, self.day, profile),
+            Source::Inline(text) => {
+                let key = input::Key::new(year, self.day, &format!("inline-{}", self.line));
+                input::provide_inline(&key, text);
+                key
+            }
+        }
+    }
+
+    pub fn run(&self, year: usize) -> Result<(), String> {
+        let day = registry::find(year, self.day)
+            .ok_or_else(|| format!("day {} is not registered", self.day))?;
+        let solver = day
+            .parts
+            .get(self.part.wrapping_sub(1))
+            .ok_or_else(|| format!("day {} has no part {}", self.day, self.part))?;
+        let output = solver(&self.key(year));
+        match matches(&self.expected, &output) {
+            true => Ok(()),
+            false => Err(format!(
+                "line {}: expected {:?}, got {:?}",
+                self.line, self.expected, output
+            )),
+        }
+    }
+}
+
+/// Numeric answers have to end the output, as a number of their own; anything
+/// else has to appear verbatim (ignoring trailing whitespace per line).
+pub fn matches(expected: &str, output: &str) -> bool {
+    if expected.parse::<i64>().is_ok() {
+        return match output.trim_end().strip_suffix(expected) {
+            Some(rest) => !rest.ends_with(|c: char| c.is_ascii_digit() || c == '-'),
+            None => false,
+        };
+    }
+    input::trim_lines(output).contains(&input::trim_lines(expected))
+}
+
+pub fn read(year: usize) -> Result<Vec<Example>, String> {
+    let path = input::examples_path(year);
+    let read = std::fs::read_to_string(&path).map_err(|e| format!("{}: {}", path.display(), e))?;
+    parse(&read)
+}
+
+pub fn parse(read: &str) -> Result<Vec<Example>, String> {
+    read.lines()
+        .enumerate()
+        .filter(|(_, l)| !l.trim().is_empty() && !l.starts_with('#'))
+        .map(|(i, l)| parse_line(i + 1, l).map_err(|e| format!("line {}: {}", i + 1, e)))
+        .collect()
+}
+
+fn parse_line(line: usize, text: &str) -> Result<Example, String> {
+    let fields = fields(text)?;
+    if fields.len() != 4 {
+        return Err(format!("expected 4 fields, found {}", fields.len()));
+    }
+    let number = |field: &str, name: &str| {
+        field
+            .parse::<usize>()
+            .map_err(|_| format!("invalid {}: {}", name, field))
+    };
+    let source = match fields[2].strip_prefix('@') {
+        Some(profile) => Source::Profile(profile.to_string()),
+        None => Source::Inline(fields[2].clone()),
+    };
+    Ok(Example {
+        line,
+        day: number(&fields[0], "day")?,
+        part: number(&fields[1], "part")?,
+        source,
+        expected: fields[3].clone(),
+    })
+}
+
+/// Splits on whitespace; double quoted fields may contain spaces and the
+/// escapes `\n`, `\"` and `\\`.
+fn fields(text: &str) -> Result<Vec<String>, String> {
+    let mut fields = vec![];
+    let mut chars = text.chars().peekable();
+    while let Some(&c) = chars.peek() {
+        if c.is_whitespace() {
+            chars.next();
+            continue;
+        }
+        let mut field = String::new();
+        if c == '"' {
+            chars.next();
+            loop {
+                match chars.next() {
+                    Some('"') => break,
+                    Some('\\') => match chars.next() {
+                        Some('n') => field.push('\n'),
+                        Some(e @ ('"' | '\\')) => field.push(e),
+                        e => return Err(format!("invalid escape \\{}", e.unwrap_or(' '))),
+                    },
+                    Some(c) => field.push(c),
+                    None => return Err("unterminated quote".to_string()),
+                }
+            }
+        } else {
+            while let Some(&c) = chars.peek() {
+                if c.is_whitespace() {
+                    break;
+                }
+                field.push(c);
+                chars.next();
+            }
+        }
+        fields.push(field);
+    }
+    Ok(fields)
+}
+
+#[cfg(test)]
+fn check(line: usize) {
+    let examples = read(input::YEAR).unwrap();
+    let example = examples.iter().find(|e| e.line == line).unwrap();
+    if let Err(err) = example.run(input::YEAR) {
+        panic!("{}", err);
+    }
+}
+
+#[cfg(test)]
+mod generated {
+    include!(concat!(env!("OUT_DIR"), "/examples.rs"));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_registered_day_has_examples() {
+        let examples = read(input::YEAR).unwrap();
+        for day in registry::days() {
+            for part in 1..=day.parts.len() {
+                assert!(
+                    examples.iter().any(|e| e.day == day.day && e.part == part),
+                    "day {} part {} has no example",
+                    day.day,
+                    part
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn parse_works() {
+        let examples =
+            parse("# comment\n\n6 1 @example 5934\n1 2 \"1\\n2 \\\"x\\\"\" \"a b\"\n").unwrap();
+        assert_eq!(
+            examples,
+            vec![
+                Example {
+                    line: 3,
+                    day: 6,
+                    part: 1,
+                    source: Source::Profile("example".to_string()),
+                    expected: "5934".to_string(),
+                },
+                Example {
+                    line: 4,
+                    day: 1,
+                    part: 2,
+                    source: Source::Inline("1\n2 \"x\"".to_string()),
+                    expected: "a b".to_string(),
+                },
+            ]
+        );
+        assert!(parse("1 1 @example").is_err());
+        assert!(parse("x 1 @example 1").is_err());
+        assert!(parse("1 1 \"open 1").is_err());
+    }
+
+    #[test]
+    fn matches_works() {
+        assert!(matches("4512", "Score 188 * Number 24 = 4512\n"));
+        assert!(matches("-3", "Total: -3"));
+        assert!(!matches("24", "Score 188 * Number 24 = 4512"));
+        assert!(!matches("512", "Score 188 * Number 24 = 4512"));
+        assert!(!matches("3", "Total: -3"));
+        assert!(!matches("7", "Increasing depth: 7 times"));
+        assert!(!matches("900", "Navigation failed: depth 900 overflows"));
+        assert!(matches("7 times", "Increasing depth: 7 times"));
+        assert!(matches("# #\n###", "Code:\n# #  \n###\n   "));
+        assert!(!matches("###", "Code:\n# #"));
+    }
+}
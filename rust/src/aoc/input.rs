@@ -1,21 +1,262 @@
+use std::collections::BTreeMap;
+use std::fmt;
 use std::fs;
+use std::io::{self, BufRead, BufReader, Cursor};
+use std::path::PathBuf;
+use std::sync::Mutex;
 
-pub fn read_file(day: usize, challenge: usize) -> String {
-    let filename = format!("../inputs/day{:02}-{}.txt", day, challenge);
-    fs::read_to_string(filename).expect("error reading input")
+use crate::aoc::paths::INPUTS;
+pub use crate::aoc::paths::{examples_path, YEAR};
+
+pub const DEFAULT_PROFILE: &str = "default";
+pub const EXAMPLE_PROFILE: &str = "example";
+
+static INLINE: Mutex<BTreeMap<Key, String>> = Mutex::new(BTreeMap::new());
+
+/// Identifies one puzzle input: `inputs/<year>/<profile>/dayNN.txt`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Key {
+    pub year: usize,
+    pub day: usize,
+    pub profile: String,
+}
+
+impl Key {
+    pub fn new(year: usize, day: usize, profile: &str) -> Key {
+        Key {
+            year,
+            day,
+            profile: profile.to_string(),
+        }
+    }
+    pub fn puzzle(day: usize) -> Key {
+        Key::new(YEAR, day, DEFAULT_PROFILE)
+    }
+    pub fn example(day: usize) -> Key {
+        Key::new(YEAR, day, EXAMPLE_PROFILE)
+    }
+    pub fn path(&self) -> PathBuf {
+        self.dir().join(format!("day{:02}.txt", self.day))
+    }
+    /// Expected output of `challenge<part>`, used by the `check` run mode.
+    pub fn answer_path(&self, part: usize) -> PathBuf {
+        self.dir()
+            .join(format!("day{:02}-{}.answer", self.day, part))
+    }
+    pub fn exists(&self) -> bool {
+        inline(self).is_some() || self.path().is_file()
+    }
+    fn dir(&self) -> PathBuf {
+        PathBuf::from(INPUTS)
+            .join(self.year.to_string())
+            .join(&self.profile)
+    }
+}
+
+impl fmt::Display for Key {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}/{}/day{:02}", self.year, self.profile, self.day)
+    }
+}
+
+/// Serves `text` instead of a file for `key`, so inline examples can be fed
+/// to the solvers.
+pub fn provide_inline(key: &Key, text: &str) {
+    INLINE.lock().unwrap().insert(key.clone(), text.to_string());
+}
+
+fn inline(key: &Key) -> Option<String> {
+    INLINE.lock().unwrap().get(key).cloned()
+}
+
+/// All input profiles available for a year, sorted by name.
+pub fn profiles(year: usize) -> Vec<String> {
+    let dir = PathBuf::from(INPUTS).join(year.to_string());
+    let mut profiles = fs::read_dir(dir)
+        .map(|entries| {
+            entries
+                .filter_map(|e| e.ok())
+                .filter(|e| e.path().is_dir())
+                .filter_map(|e| e.file_name().into_string().ok())
+                .collect::<Vec<_>>()
+        })
+        .unwrap_or_default();
+    profiles.sort();
+    profiles
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Normalisation {
+    Bom,
+    LineEndings,
+    TrailingWhitespace,
+    TrailingBlankLines,
+    FinalNewline,
 }
 
-pub fn as_ints(day: usize, challenge: usize) -> Result<Vec<i64>, std::num::ParseIntError> {
-    read_file(day, challenge)
+impl fmt::Display for Normalisation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let text = match self {
+            Normalisation::Bom => "stripped byte order mark",
+            Normalisation::LineEndings => "converted CRLF/CR line endings",
+            Normalisation::TrailingWhitespace => "trimmed trailing whitespace",
+            Normalisation::TrailingBlankLines => "removed trailing blank lines",
+            Normalisation::FinalNewline => "added final newline",
+        };
+        write!(f, "{}", text)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Normalised {
+    pub text: String,
+    pub applied: Vec<Normalisation>,
+}
+
+/// Brings raw input into the shape the solvers expect: no BOM, `\n` line
+/// endings, no trailing whitespace and exactly one final newline. Leading
+/// whitespace and blank lines between sections are kept.
+pub fn normalise(raw: &str) -> Normalised {
+    let mut applied = vec![];
+    let mut text = raw;
+    if let Some(stripped) = text.strip_prefix('\u{feff}') {
+        applied.push(Normalisation::Bom);
+        text = stripped;
+    }
+    let mut text = text.to_string();
+    if text.contains('\r') {
+        applied.push(Normalisation::LineEndings);
+        text = text.replace("\r\n", "\n").replace('\r', "\n");
+    }
+    if text.is_empty() {
+        return Normalised { text, applied };
+    }
+    let mut lines = text.split('\n').collect::<Vec<_>>();
+    let had_final_newline = lines.last() == Some(&"");
+    if had_final_newline {
+        lines.pop();
+    }
+    let mut lines = lines
+        .into_iter()
+        .map(|l| (l, l.trim_end()))
+        .collect::<Vec<_>>();
+    if lines.iter().any(|(l, trimmed)| l.len() != trimmed.len()) {
+        applied.push(Normalisation::TrailingWhitespace);
+    }
+    if lines.last().map(|(_, trimmed)| trimmed.is_empty()) == Some(true) {
+        applied.push(Normalisation::TrailingBlankLines);
+        while lines.last().map(|(_, trimmed)| trimmed.is_empty()) == Some(true) {
+            lines.pop();
+        }
+    } else if !had_final_newline {
+        applied.push(Normalisation::FinalNewline);
+    }
+    let mut text = lines
+        .iter()
+        .map(|(_, trimmed)| *trimmed)
+        .collect::<Vec<_>>()
+        .join("\n");
+    if !text.is_empty() {
+        text.push('\n');
+    }
+    Normalised { text, applied }
+}
+
+pub fn read_normalised(key: &Key) -> Normalised {
+    match inline(key) {
+        Some(text) => normalise(&text),
+        None => normalise(&fs::read_to_string(key.path()).expect("error reading input")),
+    }
+}
+
+pub fn read_file(key: &Key) -> String {
+    read_normalised(key).text
+}
+
+/// Opens the input for line-by-line reading without loading it whole.
+/// Unlike `read_file`, no normalisation is applied.
+pub fn open(key: &Key) -> io::Result<Box<dyn BufRead>> {
+    match inline(key) {
+        Some(text) => Ok(Box::new(Cursor::new(text.into_bytes()))),
+        None => Ok(Box::new(BufReader::new(fs::File::open(key.path())?))),
+    }
+}
+
+pub fn read_answer(key: &Key, part: usize) -> Option<String> {
+    fs::read_to_string(key.answer_path(part))
+        .ok()
+        .map(|a| trim_lines(&a))
+}
+
+/// Drops trailing whitespace per line and at the end, so rendered grids
+/// compare equal regardless of padding.
+pub fn trim_lines(text: &str) -> String {
+    text.lines()
+        .map(|l| l.trim_end())
+        .collect::<Vec<_>>()
+        .join("\n")
+        .trim_end()
+        .to_string()
+}
+
+pub fn as_ints(key: &Key) -> Result<Vec<i64>, std::num::ParseIntError> {
+    read_file(key)
         .lines()
         .map(|integer| integer.parse())
         .collect()
 }
 
-pub fn line_as_usize(day: usize, challenge: usize) -> Result<Vec<usize>, std::num::ParseIntError> {
-    read_file(day, challenge)
+pub fn line_as_usize(key: &Key) -> Result<Vec<usize>, std::num::ParseIntError> {
+    read_file(key)
         .trim_end()
-        .split(",")
+        .split(',')
         .map(|v| v.parse())
         .collect()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn normalise_clean_input() {
+        let n = normalise("1,2\n\n 3 4\n");
+        assert_eq!(n.text, "1,2\n\n 3 4\n");
+        assert_eq!(n.applied, vec![]);
+        assert_eq!(normalise("").text, "");
+    }
+
+    #[test]
+    fn normalise_windows_input() {
+        let n = normalise("\u{feff}6,10\r\n0,14 \r\n\t\r\nfold along y=7\r\n\r\n");
+        assert_eq!(n.text, "6,10\n0,14\n\nfold along y=7\n");
+        assert_eq!(
+            n.applied,
+            vec![
+                Normalisation::Bom,
+                Normalisation::LineEndings,
+                Normalisation::TrailingWhitespace,
+                Normalisation::TrailingBlankLines,
+            ]
+        );
+    }
+
+    #[test]
+    fn normalise_final_newline() {
+        let n = normalise("00100\n11110");
+        assert_eq!(n.text, "00100\n11110\n");
+        assert_eq!(n.applied, vec![Normalisation::FinalNewline]);
+    }
+
+    #[test]
+    fn normalise_keeps_solvers_working() {
+        let raw = input_with_crlf(&read_file(&Key::example(13)));
+        let (dots, cmds) = crate::aoc::day13::split(normalise(&raw).text);
+        assert_eq!(dots.len(), 18);
+        assert_eq!(cmds, vec![(-1, 7), (5, -1)]);
+    }
+
+    fn input_with_crlf(text: &str) -> String {
+        text.lines().map(|l| format!("{} \r\n", l)).collect()
+    }
+}
@@ -1,9 +1,17 @@
+pub mod examples;
 pub mod input;
+pub mod paths;
+pub mod registry;
+pub mod runner;
+pub mod schema;
 
+// Days marked with `allow` derive `Recap`, which expands to a `FromStr` impl
+// these lints do not expect.
 pub mod day01;
 pub mod day02;
 pub mod day03;
 pub mod day04;
+#[allow(non_local_definitions, clippy::needless_question_mark)]
 pub mod day05;
 pub mod day06;
 pub mod day07;
@@ -13,3 +21,18 @@ pub mod day10;
 pub mod day11;
 pub mod day12;
 pub mod day13;
+pub mod day14;
+pub mod day15;
+pub mod day16;
+#[allow(non_local_definitions, clippy::needless_question_mark)]
+pub mod day17;
+pub mod day18;
+pub mod day19;
+pub mod day20;
+#[allow(non_local_definitions, clippy::needless_question_mark)]
+pub mod day21;
+#[allow(non_local_definitions, clippy::needless_question_mark)]
+pub mod day22;
+pub mod day23;
+pub mod day24;
+pub mod day25;
@@ -0,0 +1,14 @@
+//! Where the puzzle inputs live. The build script includes this file as well,
+//! so it must not use anything else from the crate.
+
+use std::path::PathBuf;
+
+pub const YEAR: usize = 2021;
+pub const INPUTS: &str = "../inputs";
+
+/// Path of the year's example table, see `aoc::examples`.
+pub fn examples_path(year: usize) -> PathBuf {
+    PathBuf::from(INPUTS)
+        .join(year.to_string())
+        .join("examples.txt")
+}
@@ -0,0 +1,84 @@
+use crate::aoc::input;
+use crate::aoc::schema::Schema;
+use crate::aoc::*;
+
+pub type Solver = fn(&input::Key) -> String;
+pub type SchemaFn = fn() -> Schema;
+
+#[derive(Clone, Copy)]
+pub struct Day {
+    pub year: usize,
+    pub day: usize,
+    pub parts: [Solver; 2],
+    pub schema: SchemaFn,
+}
+
+pub fn days() -> Vec<Day> {
+    let days: [(Solver, Solver, SchemaFn); 25] = [
+        (day01::challenge1, day01::challenge2, day01::schema),
+        (day02::challenge1, day02::challenge2, day02::schema),
+        (day03::challenge1, day03::challenge2, day03::schema),
+        (day04::challenge1, day04::challenge2, day04::schema),
+        (day05::challenge1, day05::challenge2, day05::schema),
+        (day06::challenge1, day06::challenge2, day06::schema),
+        (day07::challenge1, day07::challenge2, day07::schema),
+        (day08::challenge1, day08::challenge2, day08::schema),
+        (day09::challenge1, day09::challenge2, day09::schema),
+        (day10::challenge1, day10::challenge2, day10::schema),
+        (day11::challenge1, day11::challenge2, day11::schema),
+        (day12::challenge1, day12::challenge2, day12::schema),
+        (day13::challenge1, day13::challenge2, day13::schema),
+        (day14::challenge1, day14::challenge2, day14::schema),
+        (day15::challenge1, day15::challenge2, day15::schema),
+        (day16::challenge1, day16::challenge2, day16::schema),
+        (day17::challenge1, day17::challenge2, day17::schema),
+        (day18::challenge1, day18::challenge2, day18::schema),
+        (day19::challenge1, day19::challenge2, day19::schema),
+        (day20::challenge1, day20::challenge2, day20::schema),
+        (day21::challenge1, day21::challenge2, day21::schema),
+        (day22::challenge1, day22::challenge2, day22::schema),
+        (day23::challenge1, day23::challenge2, day23::schema),
+        (day24::challenge1, day24::challenge2, day24::schema),
+        (day25::challenge1, day25::challenge2, day25::schema),
+    ];
+    days.iter()
+        .enumerate()
+        .map(|(i, (challenge1, challenge2, schema))| Day {
+            year: input::YEAR,
+            day: i + 1,
+            parts: [*challenge1, *challenge2],
+            schema: *schema,
+        })
+        .collect()
+}
+
+pub fn find(year: usize, day: usize) -> Option<Day> {
+    days().into_iter().find(|d| d.year == year && d.day == day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn days_are_keyed_by_year_and_day() {
+        let days = days();
+        assert_eq!(days.len(), 25);
+        assert_eq!(find(2021, 25).unwrap().day, 25);
+        assert!(find(2021, 26).is_none());
+        assert!(find(2020, 1).is_none());
+    }
+
+    #[test]
+    fn inputs_match_schemas() {
+        for day in days() {
+            for profile in input::profiles(day.year) {
+                let key = input::Key::new(day.year, day.day, &profile);
+                if key.exists() {
+                    let violations = (day.schema)().validate(&input::read_file(&key));
+                    assert_eq!(violations, vec![], "{}", key);
+                }
+            }
+        }
+    }
+}
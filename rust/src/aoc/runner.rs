@@ -0,0 +1,458 @@
+use crate::aoc::input;
+use crate::aoc::registry;
+use crate::aoc::schema;
+
+use std::panic;
+use std::sync::mpsc;
+use std::thread;
+use std::time::Duration;
+
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(60);
+const SOLVER_THREAD: &str = "solver";
+/// Spawned threads only get 2 MiB by default; recursive solvers need the
+/// 8 MiB they would have on the main thread.
+const SOLVER_STACK: usize = 8 * 1024 * 1024;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Mode {
+    Run,
+    Check,
+    Validate,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Options {
+    pub mode: Mode,
+    pub years: Vec<usize>,
+    pub days: Vec<usize>,
+    pub profiles: Vec<String>,
+    /// Time limit per part, `None` waits forever.
+    pub timeout: Option<Duration>,
+}
+
+pub const USAGE: &str = "usage: adventofcode2021 [run|check|validate] [--year YEAR]... [--day DAY]... [--profile NAME]... [--timeout SECONDS]
+
+  --timeout SECONDS  stop waiting for a part after this long (default 60, 0
+                     waits forever). A timed out solver cannot be stopped: it
+                     keeps running, and using CPU and memory, until the
+                     whole run ends.";
+
+impl Options {
+    pub fn parse(args: &[String]) -> Result<Options, String> {
+        let mut options = Options {
+            mode: Mode::Run,
+            years: vec![],
+            days: vec![],
+            profiles: vec![],
+            timeout: Some(DEFAULT_TIMEOUT),
+        };
+        let mut args = args.iter().peekable();
+        match args.peek().map(|a| a.as_str()) {
+            Some("run") => {
+                args.next();
+            }
+            Some("check") => {
+                options.mode = Mode::Check;
+                args.next();
+            }
+            Some("validate") => {
+                options.mode = Mode::Validate;
+                args.next();
+            }
+            _ => {}
+        }
+        while let Some(arg) = args.next() {
+            let mut value = || {
+                args.next()
+                    .ok_or_else(|| format!("missing value for {}", arg))
+            };
+            match arg.as_str() {
+                "--year" => options.years.push(parse_number(arg, value()?)?),
+                "--day" => options.days.push(parse_number(arg, value()?)?),
+                "--profile" => options.profiles.push(value()?.to_string()),
+                "--timeout" => {
+                    options.timeout = match parse_number(arg, value()?)? {
+                        0 => None,
+                        secs => Some(Duration::from_secs(secs as u64)),
+                    }
+                }
+                _ => return Err(format!("unknown argument: {}", arg)),
+            }
+        }
+        Ok(options)
+    }
+
+    fn selected_days(&self) -> Vec<registry::Day> {
+        registry::days()
+            .into_iter()
+            .filter(|d| self.years.is_empty() || self.years.contains(&d.year))
+            .filter(|d| self.days.is_empty() || self.days.contains(&d.day))
+            .collect()
+    }
+
+    fn selected_profiles(&self, year: usize) -> Vec<String> {
+        match (self.profiles.is_empty(), &self.mode) {
+            (false, _) => self.profiles.clone(),
+            (true, Mode::Run) => vec![input::DEFAULT_PROFILE.to_string()],
+            (true, Mode::Check | Mode::Validate) => input::profiles(year),
+        }
+    }
+}
+
+fn parse_number(arg: &str, value: &str) -> Result<usize, String> {
+    value
+        .parse()
+        .map_err(|_| format!("invalid value for {}: {}", arg, value))
+}
+
+pub fn run(options: &Options) {
+    for day in options.selected_days() {
+        for profile in options.selected_profiles(day.year) {
+            let key = input::Key::new(day.year, day.day, &profile);
+            if profile == input::DEFAULT_PROFILE && options.profiles.is_empty() {
+                println!("day{:02}:", day.day);
+            } else {
+                println!("{}:", key);
+            }
+            if !key.exists() {
+                println!("...missing input {}", key.path().display());
+                continue;
+            }
+            if let Some(applied) = normalisations(&key) {
+                println!("...normalised: {}", applied);
+            }
+            let violations = violations(&day, &key);
+            if !violations.is_empty() {
+                for v in violations {
+                    println!("...invalid input {}:{}", key.path().display(), v);
+                }
+                continue;
+            }
+            for (part, solver) in day.parts.iter().enumerate() {
+                println!(
+                    "...{}: {}",
+                    part + 1,
+                    execute(*solver, &key, options.timeout)
+                );
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Outcome {
+    Solved(String),
+    Timeout(Duration),
+    Panicked(String),
+}
+
+impl std::fmt::Display for Outcome {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Outcome::Solved(result) => write!(f, "{}", result),
+            Outcome::Timeout(limit) => write!(f, "timeout after {}s", limit.as_secs_f64()),
+            Outcome::Panicked(message) => write!(f, "panicked: {}", message),
+        }
+    }
+}
+
+/// Runs a solver on its own thread so a panic or a solver that never returns
+/// only costs this part. Threads cannot be killed, so a timed out solver is
+/// left running and only dies with the process.
+pub fn execute(solver: registry::Solver, key: &input::Key, timeout: Option<Duration>) -> Outcome {
+    let (tx, rx) = mpsc::channel();
+    let key = key.clone();
+    let spawned = thread::Builder::new()
+        .name(SOLVER_THREAD.to_string())
+        .stack_size(SOLVER_STACK)
+        .spawn(move || {
+            let result = panic::catch_unwind(|| solver(&key));
+            tx.send(result.map_err(|payload| panic_message(payload.as_ref())))
+                .ok();
+        });
+    if let Err(err) = spawned {
+        return Outcome::Panicked(format!("could not spawn solver: {}", err));
+    }
+    let vanished = || Outcome::Panicked("solver vanished".to_string());
+    let received = match timeout {
+        Some(limit) => rx.recv_timeout(limit).map_err(|err| match err {
+            mpsc::RecvTimeoutError::Timeout => Outcome::Timeout(limit),
+            mpsc::RecvTimeoutError::Disconnected => vanished(),
+        }),
+        None => rx.recv().map_err(|_| vanished()),
+    };
+    match received {
+        Ok(Ok(result)) => Outcome::Solved(result),
+        Ok(Err(message)) => Outcome::Panicked(message),
+        Err(outcome) => outcome,
+    }
+}
+
+fn panic_message(payload: &(dyn std::any::Any + Send)) -> String {
+    if let Some(message) = payload.downcast_ref::<&str>() {
+        return message.to_string();
+    }
+    if let Some(message) = payload.downcast_ref::<String>() {
+        return message.clone();
+    }
+    "unknown panic".to_string()
+}
+
+/// Keeps the default panic output for everything but solver threads, whose
+/// panics are reported as part of the run.
+pub fn quiet_solver_panics() {
+    let default = panic::take_hook();
+    panic::set_hook(Box::new(move |info| {
+        if thread::current().name() != Some(SOLVER_THREAD) {
+            default(info);
+        }
+    }));
+}
+
+fn violations(day: &registry::Day, key: &input::Key) -> Vec<schema::Violation> {
+    (day.schema)().validate(&input::read_file(key))
+}
+
+/// Checks the selected inputs against their day's schema and prints every
+/// violation. Returns false if any input is invalid.
+pub fn validate(options: &Options) -> bool {
+    let mut inputs = 0;
+    let mut count = 0;
+    for day in options.selected_days() {
+        for profile in options.selected_profiles(day.year) {
+            let key = input::Key::new(day.year, day.day, &profile);
+            if !key.exists() {
+                continue;
+            }
+            inputs += 1;
+            for v in violations(&day, &key) {
+                count += 1;
+                println!("{}:{}", key.path().display(), v);
+            }
+        }
+    }
+    println!("{} inputs validated, {} violations", inputs, count);
+    count == 0
+}
+
+fn normalisations(key: &input::Key) -> Option<String> {
+    let applied = input::read_normalised(key).applied;
+    if applied.is_empty() {
+        return None;
+    }
+    Some(
+        applied
+            .iter()
+            .map(|n| n.to_string())
+            .collect::<Vec<_>>()
+            .join(", "),
+    )
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Verdict {
+    Ok,
+    Mismatch(String),
+    Unchecked,
+}
+
+pub fn verdict(key: &input::Key, part: usize, result: &str) -> Verdict {
+    match input::read_answer(key, part) {
+        Some(expected) if expected == input::trim_lines(result) => Verdict::Ok,
+        Some(expected) => Verdict::Mismatch(expected),
+        None => Verdict::Unchecked,
+    }
+}
+
+/// Runs the selected days against every selected profile and compares the
+/// results with the recorded answers. Returns false if any answer differs.
+pub fn check(options: &Options) -> bool {
+    let mut mismatches = 0;
+    let mut checked = 0;
+    let mut invalid = 0;
+    let mut failed = 0;
+    for day in options.selected_days() {
+        for profile in options.selected_profiles(day.year) {
+            let key = input::Key::new(day.year, day.day, &profile);
+            if !key.exists() {
+                continue;
+            }
+            if let Some(applied) = normalisations(&key) {
+                println!("{} normalised: {}", key, applied);
+            }
+            let violations = violations(&day, &key);
+            if !violations.is_empty() {
+                invalid += 1;
+                for v in violations {
+                    println!("{} invalid: {}:{}", key, key.path().display(), v);
+                }
+                continue;
+            }
+            for (part, solver) in day.parts.iter().enumerate() {
+                let result = match execute(*solver, &key, options.timeout) {
+                    Outcome::Solved(result) => result,
+                    failure => {
+                        failed += 1;
+                        println!("{} part {}: {}", key, part + 1, failure);
+                        continue;
+                    }
+                };
+                match verdict(&key, part + 1, &result) {
+                    Verdict::Ok => {
+                        checked += 1;
+                        println!("{} part {}: ok", key, part + 1);
+                    }
+                    Verdict::Mismatch(expected) => {
+                        checked += 1;
+                        mismatches += 1;
+                        println!(
+                            "{} part {}: MISMATCH\n    expected: {}\n    got:      {}",
+                            key,
+                            part + 1,
+                            expected,
+                            result
+                        );
+                    }
+                    Verdict::Unchecked => {
+                        println!("{} part {}: unchecked ({})", key, part + 1, result)
+                    }
+                }
+            }
+        }
+    }
+    println!(
+        "{} answers checked, {} mismatches, {} invalid inputs, {} failed",
+        checked, mismatches, invalid, failed
+    );
+    mismatches == 0 && invalid == 0 && failed == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(args: &[&str]) -> Vec<String> {
+        args.iter().map(|a| a.to_string()).collect()
+    }
+
+    #[test]
+    fn parse_defaults_to_run() {
+        let options = Options::parse(&args(&[])).unwrap();
+        assert_eq!(options.mode, Mode::Run);
+        assert_eq!(options.selected_days().len(), 25);
+        assert_eq!(options.selected_profiles(2021), vec!["default"]);
+    }
+
+    #[test]
+    fn parse_check_with_filters() {
+        let options = Options::parse(&args(&[
+            "check",
+            "--year",
+            "2021",
+            "--day",
+            "4",
+            "--profile",
+            "example",
+            "--profile",
+            "alice",
+        ]))
+        .unwrap();
+        assert_eq!(options.mode, Mode::Check);
+        assert_eq!(options.years, vec![2021]);
+        assert_eq!(options.selected_days().len(), 1);
+        assert_eq!(options.selected_profiles(2021), vec!["example", "alice"]);
+    }
+
+    #[test]
+    fn parse_timeout() {
+        let options = Options::parse(&args(&[])).unwrap();
+        assert_eq!(options.timeout, Some(DEFAULT_TIMEOUT));
+        let options = Options::parse(&args(&["--timeout", "5"])).unwrap();
+        assert_eq!(options.timeout, Some(Duration::from_secs(5)));
+        let options = Options::parse(&args(&["--timeout", "0"])).unwrap();
+        assert_eq!(options.timeout, None);
+    }
+
+    #[test]
+    fn execute_isolates_solvers() {
+        let key = input::Key::example(4);
+        let generous = Some(Duration::from_secs(60));
+        assert_eq!(
+            execute(|key| format!("day {}", key.day), &key, generous),
+            Outcome::Solved("day 4".to_string())
+        );
+        assert_eq!(
+            execute(|_| panic!("bad input"), &key, generous),
+            Outcome::Panicked("bad input".to_string())
+        );
+        assert_eq!(
+            execute(|key| panic!("bad day {}", key.day), &key, None),
+            Outcome::Panicked("bad day 4".to_string())
+        );
+        assert_eq!(
+            execute(
+                |_| loop {
+                    thread::sleep(Duration::from_millis(10))
+                },
+                &key,
+                Some(Duration::from_millis(100))
+            ),
+            Outcome::Timeout(Duration::from_millis(100))
+        );
+    }
+
+    #[test]
+    fn execute_grants_a_main_sized_stack() {
+        fn descend(depth: usize) -> usize {
+            let frame = std::hint::black_box([depth as u8; 1024]);
+            match depth {
+                0 => frame[0] as usize,
+                _ => descend(depth - 1) + frame[1023] as usize,
+            }
+        }
+        assert!(matches!(
+            execute(|_| descend(1500).to_string(), &input::Key::example(1), None),
+            Outcome::Solved(_)
+        ));
+    }
+
+    #[test]
+    fn parse_errors() {
+        assert!(Options::parse(&args(&["--day"])).is_err());
+        assert!(Options::parse(&args(&["--day", "x"])).is_err());
+        assert!(Options::parse(&args(&["--frobnicate"])).is_err());
+    }
+
+    #[test]
+    fn parse_validate() {
+        let options = Options::parse(&args(&["validate", "--day", "12"])).unwrap();
+        assert_eq!(options.mode, Mode::Validate);
+        assert_eq!(options.days, vec![12]);
+        assert!(options.selected_profiles(2021).len() >= 2);
+    }
+
+    #[test]
+    fn check_uses_all_profiles() {
+        let options = Options::parse(&args(&["check"])).unwrap();
+        let profiles = options.selected_profiles(2021);
+        assert!(profiles.contains(&"default".to_string()));
+        assert!(profiles.contains(&"example".to_string()));
+    }
+
+    #[test]
+    fn verdict_compares_recorded_answers() {
+        let key = input::Key::example(4);
+        assert_eq!(
+            verdict(&key, 1, "Score 188 * Number 24 = 4512"),
+            Verdict::Ok
+        );
+        assert_eq!(
+            verdict(&key, 1, "Score 1 * Number 1 = 1"),
+            Verdict::Mismatch("Score 188 * Number 24 = 4512".to_string())
+        );
+        assert_eq!(
+            verdict(&input::Key::new(2021, 4, "nobody"), 1, ""),
+            Verdict::Unchecked
+        );
+    }
+}
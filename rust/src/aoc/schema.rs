@@ -0,0 +1,451 @@
+use std::fmt;
+
+/// A single piece of a line shape. Lines are matched token by token from the
+/// left, so a failing token pinpoints the column of the first bad character.
+#[derive(Debug, Clone)]
+pub enum Token {
+    Literal(&'static str),
+    OneOf(&'static [&'static str]),
+    /// A decimal number within an inclusive range.
+    Number(i64, i64),
+    /// Exactly one character taken from the given set.
+    Char(&'static str),
+    /// One or more characters taken from the given set.
+    Chars(&'static str),
+    /// One or more spaces.
+    Spaces,
+    Optional(Box<Token>),
+    List {
+        item: Box<Token>,
+        separator: Box<Token>,
+        min: usize,
+        max: usize,
+    },
+}
+
+impl Token {
+    pub fn list(item: Token, separator: Token, min: usize, max: usize) -> Token {
+        Token::List {
+            item: Box::new(item),
+            separator: Box::new(separator),
+            min,
+            max,
+        }
+    }
+    pub fn optional(token: Token) -> Token {
+        Token::Optional(Box::new(token))
+    }
+
+    /// Matches the token at byte offset `pos`, returning the offset after it
+    /// or the offset and description of the mismatch.
+    fn matches(&self, line: &str, pos: usize) -> Result<usize, (usize, String)> {
+        let rest = &line[pos..];
+        match self {
+            Token::Literal(literal) => match rest.starts_with(literal) {
+                true => Ok(pos + literal.len()),
+                false => Err((pos, format!("expected {:?}", literal))),
+            },
+            Token::OneOf(words) => {
+                let mut candidates = words.to_vec();
+                candidates.sort_by_key(|w| std::cmp::Reverse(w.len()));
+                candidates
+                    .iter()
+                    .find(|w| rest.starts_with(*w))
+                    .map(|w| pos + w.len())
+                    .ok_or_else(|| (pos, format!("expected one of {}", words.join(", "))))
+            }
+            Token::Number(min, max) => {
+                let sign = match *min < 0 && rest.starts_with('-') {
+                    true => 1,
+                    false => 0,
+                };
+                let digits = rest[sign..]
+                    .chars()
+                    .take_while(|c| c.is_ascii_digit())
+                    .count();
+                if digits == 0 {
+                    return Err((pos, "expected a number".to_string()));
+                }
+                let end = pos + sign + digits;
+                match line[pos..end].parse::<i64>() {
+                    Ok(n) if n >= *min && n <= *max => Ok(end),
+                    _ => Err((
+                        pos,
+                        format!("number {} out of range {}..={}", &line[pos..end], min, max),
+                    )),
+                }
+            }
+            Token::Char(set) => match rest.chars().next() {
+                Some(c) if set.contains(c) => Ok(pos + c.len_utf8()),
+                _ => Err((pos, format!("expected one of the characters {:?}", set))),
+            },
+            Token::Chars(set) => match rest.chars().take_while(|c| set.contains(*c)).count() {
+                0 => Err((pos, format!("expected one of the characters {:?}", set))),
+                n => Ok(pos + n),
+            },
+            Token::Spaces => match rest.chars().take_while(|c| *c == ' ').count() {
+                0 => Err((pos, "expected a space".to_string())),
+                n => Ok(pos + n),
+            },
+            Token::Optional(token) => Ok(token.matches(line, pos).unwrap_or(pos)),
+            Token::List {
+                item,
+                separator,
+                min,
+                max,
+            } => {
+                let mut end = item.matches(line, pos)?;
+                let mut count = 1;
+                while let Ok(next) = separator
+                    .matches(line, end)
+                    .and_then(|sep| item.matches(line, sep))
+                {
+                    end = next;
+                    count += 1;
+                }
+                if count < *min || count > *max {
+                    return Err((
+                        pos,
+                        format!("expected {}..={} items, found {}", min, max, count),
+                    ));
+                }
+                Ok(end)
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Section {
+    pub name: &'static str,
+    /// Shape of the first line, if it differs from the rest.
+    pub header: Option<Vec<Token>>,
+    pub line: Vec<Token>,
+    pub min_lines: usize,
+    pub max_lines: usize,
+    /// All lines of the section must have the same width.
+    pub rectangular: bool,
+    /// The section may occur any number of times (only for the last one).
+    pub repeated: bool,
+}
+
+impl Section {
+    pub fn new(name: &'static str, line: Vec<Token>) -> Section {
+        Section {
+            name,
+            header: None,
+            line,
+            min_lines: 1,
+            max_lines: usize::MAX,
+            rectangular: false,
+            repeated: false,
+        }
+    }
+    pub fn header(mut self, header: Vec<Token>) -> Section {
+        self.header = Some(header);
+        self
+    }
+    pub fn lines(mut self, min: usize, max: usize) -> Section {
+        self.min_lines = min;
+        self.max_lines = max;
+        self
+    }
+    pub fn rectangular(mut self) -> Section {
+        self.rectangular = true;
+        self
+    }
+    pub fn repeated(mut self) -> Section {
+        self.repeated = true;
+        self
+    }
+}
+
+pub type Check = fn(read: &str) -> Vec<Violation>;
+
+/// Shape of a day's input: blank-line separated sections plus optional
+/// whole-input checks.
+#[derive(Debug, Clone)]
+pub struct Schema {
+    pub sections: Vec<Section>,
+    pub checks: Vec<Check>,
+}
+
+impl Schema {
+    pub fn lines(line: Vec<Token>) -> Schema {
+        Schema::sections(vec![Section::new("lines", line)])
+    }
+    pub fn grid(chars: &'static str) -> Schema {
+        Schema::sections(vec![
+            Section::new("grid", vec![Token::Chars(chars)]).rectangular()
+        ])
+    }
+    pub fn sections(sections: Vec<Section>) -> Schema {
+        Schema {
+            sections,
+            checks: vec![],
+        }
+    }
+    pub fn check(mut self, check: Check) -> Schema {
+        self.checks.push(check);
+        self
+    }
+
+    pub fn validate(&self, read: &str) -> Vec<Violation> {
+        let mut violations = vec![];
+        let lines = read.lines().collect::<Vec<_>>();
+        if lines.is_empty() {
+            violations.push(Violation::new(1, 1, "input is empty"));
+            return violations;
+        }
+
+        let mut groups: Vec<Vec<(usize, &str)>> = vec![vec![]];
+        for (i, line) in lines.iter().enumerate() {
+            match line.is_empty() {
+                true => groups.push(vec![]),
+                false => groups.last_mut().unwrap().push((i + 1, *line)),
+            }
+        }
+
+        let repeated = self.sections.last().map(|s| s.repeated) == Some(true);
+        let count_ok = match repeated {
+            true => groups.len() >= self.sections.len(),
+            false => groups.len() == self.sections.len(),
+        };
+        if !count_ok {
+            violations.push(Violation::new(
+                lines.len(),
+                1,
+                &format!(
+                    "expected {}{} blank-line separated sections, found {}",
+                    match repeated {
+                        true => "at least ",
+                        false => "",
+                    },
+                    self.sections.len(),
+                    groups.len()
+                ),
+            ));
+        }
+
+        let mut start = 1;
+        for (i, group) in groups.iter().enumerate() {
+            let section = match self.sections.get(i).or_else(|| match repeated {
+                true => self.sections.last(),
+                false => None,
+            }) {
+                Some(section) => section,
+                None => break,
+            };
+            violations.append(&mut validate_section(section, group, start));
+            start = group.last().map(|(n, _)| n + 2).unwrap_or(start + 1);
+        }
+
+        for check in self.checks.iter() {
+            violations.append(&mut check(read));
+        }
+        violations.sort_by_key(|v| (v.line, v.column));
+        violations
+    }
+}
+
+fn validate_section(section: &Section, group: &[(usize, &str)], start: usize) -> Vec<Violation> {
+    let mut violations = vec![];
+    if group.len() < section.min_lines || group.len() > section.max_lines {
+        let expected = match section.min_lines == section.max_lines {
+            true => section.min_lines.to_string(),
+            false if section.max_lines == usize::MAX => format!("at least {}", section.min_lines),
+            false => format!("{}..={}", section.min_lines, section.max_lines),
+        };
+        violations.push(Violation::new(
+            group.first().map(|(n, _)| *n).unwrap_or(start),
+            1,
+            &format!(
+                "section `{}` has {} lines, expected {}",
+                section.name,
+                group.len(),
+                expected
+            ),
+        ));
+    }
+    for (i, (number, line)) in group.iter().enumerate() {
+        let tokens = match (i, &section.header) {
+            (0, Some(header)) => header,
+            _ => &section.line,
+        };
+        if let Err((pos, message)) = match_line(tokens, line) {
+            violations.push(Violation::new(*number, pos + 1, &message));
+        }
+    }
+    if section.rectangular {
+        if let Some((first, width)) = group.first().map(|(n, l)| (*n, l.chars().count())) {
+            for (number, line) in group.iter() {
+                let w = line.chars().count();
+                if w != width {
+                    violations.push(Violation::new(
+                        *number,
+                        w.min(width) + 1,
+                        &format!(
+                            "line has width {}, expected {} like line {}",
+                            w, width, first
+                        ),
+                    ));
+                }
+            }
+        }
+    }
+    violations
+}
+
+fn match_line(tokens: &[Token], line: &str) -> Result<(), (usize, String)> {
+    let mut pos = 0;
+    for token in tokens {
+        pos = token.matches(line, pos)?;
+    }
+    match pos < line.len() {
+        true => Err((pos, format!("unexpected {:?}", &line[pos..]))),
+        false => Ok(()),
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Violation {
+    pub line: usize,
+    pub column: usize,
+    pub message: String,
+}
+
+impl Violation {
+    pub fn new(line: usize, column: usize, message: &str) -> Violation {
+        Violation {
+            line,
+            column,
+            message: message.to_string(),
+        }
+    }
+}
+
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}:{}: {}", self.line, self.column, self.message)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn command() -> Schema {
+        Schema::lines(vec![
+            Token::OneOf(&["forward", "down", "up"]),
+            Token::Literal(" "),
+            Token::Number(0, 9),
+        ])
+    }
+
+    #[test]
+    fn validate_lines() {
+        assert_eq!(command().validate("forward 5\nup 3\n"), vec![]);
+        assert_eq!(
+            command().validate("forward 5\nupp 3\nback 1\ndown 12\ndown\n"),
+            vec![
+                Violation::new(2, 3, "expected \" \""),
+                Violation::new(3, 1, "expected one of forward, down, up"),
+                Violation::new(4, 6, "number 12 out of range 0..=9"),
+                Violation::new(5, 5, "expected \" \""),
+            ]
+        );
+        assert_eq!(
+            command().validate(""),
+            vec![Violation::new(1, 1, "input is empty")]
+        );
+    }
+
+    #[test]
+    fn validate_list() {
+        let schema = Schema::lines(vec![
+            Token::optional(Token::Spaces),
+            Token::list(Token::Number(0, 99), Token::Spaces, 3, 3),
+        ]);
+        assert_eq!(schema.validate(" 1  2 33\n"), vec![]);
+        assert_eq!(
+            schema.validate("1 2\n1 2 3 4\n"),
+            vec![
+                Violation::new(1, 1, "expected 3..=3 items, found 2"),
+                Violation::new(2, 1, "expected 3..=3 items, found 4"),
+            ]
+        );
+    }
+
+    #[test]
+    fn validate_char() {
+        let schema = Schema::lines(vec![
+            Token::Char("AB"),
+            Token::Char("AB"),
+            Token::Literal(" -> "),
+            Token::Char("AB"),
+        ]);
+        assert_eq!(schema.validate("AB -> A\n"), vec![]);
+        assert_eq!(
+            schema.validate("A -> B\nABA -> B\nAB -> BA\n"),
+            vec![
+                Violation::new(1, 2, "expected one of the characters \"AB\""),
+                Violation::new(2, 3, "expected \" -> \""),
+                Violation::new(3, 8, "unexpected \"A\""),
+            ]
+        );
+    }
+
+    #[test]
+    fn validate_grid() {
+        let schema = Schema::grid("0123456789");
+        assert_eq!(schema.validate("123\n456\n"), vec![]);
+        assert_eq!(
+            schema.validate("123\n4a6\n45\n"),
+            vec![
+                Violation::new(2, 2, "unexpected \"a6\""),
+                Violation::new(3, 3, "line has width 2, expected 3 like line 1"),
+            ]
+        );
+    }
+
+    #[test]
+    fn validate_sections() {
+        let schema = Schema::sections(vec![
+            Section::new("numbers", vec![Token::Number(0, 9)]).lines(1, 1),
+            Section::new("letters", vec![Token::Chars("ab")])
+                .lines(2, 2)
+                .repeated(),
+        ]);
+        assert_eq!(schema.validate("1\n\na\nb\n\nb\nb\n"), vec![]);
+        assert_eq!(
+            schema.validate("1\n2\n\na\n"),
+            vec![
+                Violation::new(1, 1, "section `numbers` has 2 lines, expected 1"),
+                Violation::new(4, 1, "section `letters` has 1 lines, expected 2"),
+            ]
+        );
+        assert_eq!(
+            schema.validate("1\n"),
+            vec![Violation::new(
+                1,
+                1,
+                "expected at least 2 blank-line separated sections, found 1"
+            )]
+        );
+    }
+
+    #[test]
+    fn validate_header() {
+        let schema = Schema::sections(vec![Section::new("items", vec![Token::Number(0, 9)])
+            .header(vec![Token::Literal("items:")])
+            .repeated()]);
+        assert_eq!(schema.validate("items:\n1\n2\n\nitems:\n3\n"), vec![]);
+        assert_eq!(
+            schema.validate("items:\n1\n\n2\nitems:\n"),
+            vec![
+                Violation::new(4, 1, "expected \"items:\""),
+                Violation::new(5, 1, "expected a number"),
+            ]
+        );
+    }
+}
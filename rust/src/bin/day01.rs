@@ -1,5 +1,59 @@
 use adventofcode2021::aoc::*;
 
+const USAGE: &str = "usage: day01 [--window <n>] [--threshold <depth>] [--format <text|json>]
+
+  Without options, solves both parts. With any option, prints the depth
+  profile of the puzzle input instead.
+
+  --window <n>          depths per window for spikes and trenches (default 3)
+  --threshold <depth>   a depth further than this from the mean of the
+                        window before it is a spike (default 100)
+  --format <text|json>  print the profile as text (default) or as JSON";
+
+fn fail(message: &str, code: i32) -> ! {
+    eprintln!("{}", message);
+    std::process::exit(code);
+}
+
 fn main() {
-    day01::main(1);
+    let key = input::Key::puzzle(1);
+    let args = std::env::args().skip(1).collect::<Vec<_>>();
+    if args.is_empty() {
+        return day01::main(&key);
+    }
+    let mut window = 3;
+    let mut threshold = 100.0;
+    let mut json = false;
+    for pair in args.chunks(2) {
+        match pair {
+            [flag, n] if flag == "--window" => match n.parse() {
+                Ok(n) if n > 0 => window = n,
+                _ => fail(&format!("invalid window {:?}", n), 2),
+            },
+            [flag, depth] if flag == "--threshold" => match depth.parse() {
+                Ok(depth) => threshold = depth,
+                Err(_) => fail(&format!("invalid threshold {:?}", depth), 2),
+            },
+            [flag, format] if flag == "--format" => match format.as_str() {
+                "text" => json = false,
+                "json" => json = true,
+                _ => fail(&format!("unknown format {:?}", format), 2),
+            },
+            _ => fail(USAGE, 2),
+        }
+    }
+    let depths = input::open(&key)
+        .and_then(|reader| day01::depths(reader).collect::<std::io::Result<Vec<_>>>())
+        .unwrap_or_else(|err| fail(&err.to_string(), 1));
+    if depths.is_empty() {
+        fail("the puzzle input holds no depths", 1);
+    }
+    let report = day01::profile(depths, window, threshold);
+    match json {
+        true => match serde_json::to_string_pretty(&report) {
+            Ok(json) => println!("{}", json),
+            Err(err) => fail(&err.to_string(), 1),
+        },
+        false => println!("{}", report),
+    }
 }
@@ -1,5 +1,67 @@
 use adventofcode2021::aoc::*;
 
+const USAGE: &str = "usage: day02 [--model <name>] [--surface <mode>] [--trace <file.csv|file.svg>] [--script <file>]
+
+  --model <name>     navigation model: simple or aim (default aim)
+  --surface <mode>   what happens above the surface: allow (default) keeps
+                     negative depths, clamp stops at depth 0, error aborts
+  --trace <file>     write every position as CSV, or as SVG for *.svg
+  --script <file>    read a command script instead of the puzzle input";
+
+fn fail(message: &str, code: i32) -> ! {
+    eprintln!("{}", message);
+    std::process::exit(code);
+}
+
 fn main() {
-    day02::main(2);
+    let key = input::Key::puzzle(2);
+    let args = std::env::args().skip(1).collect::<Vec<_>>();
+    if args.is_empty() {
+        return day02::main(&key);
+    }
+    let mut model: Box<dyn day02::Navigator> = Box::new(day02::Aim);
+    let mut surface = day02::Surface::Allow;
+    let mut trace_path = None;
+    let mut script_path = None;
+    for pair in args.chunks(2) {
+        match pair {
+            [flag, name] if flag == "--model" => match day02::model(name) {
+                Some(m) => model = m,
+                None => {
+                    let names = day02::models().iter().map(|m| m.name()).collect::<Vec<_>>();
+                    let message = format!(
+                        "unknown model {:?}, expected one of: {}",
+                        name,
+                        names.join(", ")
+                    );
+                    fail(&message, 2);
+                }
+            },
+            [flag, mode] if flag == "--surface" => match mode.parse() {
+                Ok(mode) => surface = mode,
+                Err(err) => fail(&err, 2),
+            },
+            [flag, path] if flag == "--trace" => trace_path = Some(path.clone()),
+            [flag, path] if flag == "--script" => script_path = Some(path.clone()),
+            _ => fail(USAGE, 2),
+        }
+    }
+    let commands = match &script_path {
+        Some(path) => std::fs::read_to_string(path)
+            .map_err(|err| format!("error reading {}: {}", path, err))
+            .and_then(|script| day02::read_script(&script).map_err(|err| err.to_string())),
+        None => day02::read_commands(&input::read_file(&key)).map_err(|err| err.to_string()),
+    };
+    let commands = commands.unwrap_or_else(|err| fail(&err, 1));
+    let (trace, answer) = model.record(&commands, surface);
+    println!("...{}: {}", model.name(), answer);
+    if let Some(path) = trace_path {
+        let export = match path.ends_with(".svg") {
+            true => trace.to_svg(),
+            false => trace.to_csv(),
+        };
+        if let Err(err) = std::fs::write(&path, export) {
+            fail(&format!("error writing {}: {}", path, err), 1);
+        }
+    }
 }
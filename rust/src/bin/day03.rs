@@ -1,5 +1,5 @@
 use adventofcode2021::aoc::*;
 
 fn main() {
-    day03::main(3);
+    day03::main(&input::Key::puzzle(3));
 }
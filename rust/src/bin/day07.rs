@@ -1,5 +1,5 @@
 use adventofcode2021::aoc::*;
 
 fn main() {
-    day07::main(7);
+    day07::main(&input::Key::puzzle(7));
 }
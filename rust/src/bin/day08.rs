@@ -1,5 +1,5 @@
 use adventofcode2021::aoc::*;
 
 fn main() {
-    day08::main(8);
+    day08::main(&input::Key::puzzle(8));
 }
@@ -1,5 +1,5 @@
 use adventofcode2021::aoc::*;
 
 fn main() {
-    day10::main(10);
+    day10::main(&input::Key::puzzle(10));
 }
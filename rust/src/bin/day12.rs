@@ -1,5 +1,5 @@
 use adventofcode2021::aoc::*;
 
 fn main() {
-    day12::main(12);
+    day12::main(&input::Key::puzzle(12));
 }
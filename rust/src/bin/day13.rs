@@ -1,5 +1,5 @@
 use adventofcode2021::aoc::*;
 
 fn main() {
-    day13::main(13);
+    day13::main(&input::Key::puzzle(13));
 }
@@ -0,0 +1,18 @@
+use adventofcode2021::aoc::*;
+
+const USAGE: &str = "usage: day16 [--expression]
+
+  --expression   print the transmission as an expression with its value";
+
+fn main() {
+    let key = input::Key::puzzle(16);
+    let args = std::env::args().skip(1).collect::<Vec<_>>();
+    match args.iter().map(|a| a.as_str()).collect::<Vec<_>>()[..] {
+        [] => day16::main(&key),
+        ["--expression"] => println!("{}", day16::expression(&key)),
+        _ => {
+            eprintln!("{}", USAGE);
+            std::process::exit(2);
+        }
+    }
+}
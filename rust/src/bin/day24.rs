@@ -0,0 +1,5 @@
+use adventofcode2021::aoc::*;
+
+fn main() {
+    day24::main(&input::Key::puzzle(24));
+}
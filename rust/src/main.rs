@@ -1,27 +1,26 @@
-mod aoc;
-
-use aoc::*;
-
-extern crate pest_derive;
+use adventofcode2021::aoc::runner;
 
 fn main() {
-    let days = [
-        day01::main,
-        day02::main,
-        day03::main,
-        day04::main,
-        day05::main,
-        day06::main,
-        day07::main,
-        day08::main,
-        day09::main,
-        day10::main,
-        day11::main,
-        day12::main,
-        day13::main,
-    ];
-    for (i, day) in days.iter().enumerate() {
-        println!("day{:02}:", i + 1);
-        day(i + 1);
+    let args = std::env::args().skip(1).collect::<Vec<_>>();
+    let options = match runner::Options::parse(&args) {
+        Ok(options) => options,
+        Err(err) => {
+            eprintln!("{}\n{}", err, runner::USAGE);
+            std::process::exit(2);
+        }
+    };
+    runner::quiet_solver_panics();
+    match options.mode {
+        runner::Mode::Run => runner::run(&options),
+        runner::Mode::Check => {
+            if !runner::check(&options) {
+                std::process::exit(1);
+            }
+        }
+        runner::Mode::Validate => {
+            if !runner::validate(&options) {
+                std::process::exit(1);
+            }
+        }
     }
 }